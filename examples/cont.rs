@@ -14,10 +14,10 @@ fn window_conf() -> Conf {
 async fn main() {
     let poppins = load_ttf_font("examples/poppins.ttf").await.unwrap();
     let label = Label::new("Inside a Container!".to_string(), Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone()), 32.0);
-    let button = Button::new(400.0, 80.0, "eeeeeeee".to_string(), Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.75, 0.5, 1.0), Some(poppins.clone()));
+    let button = Button::new(400.0, 80.0, "eeeeeeee".to_string(), Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.75, 0.5, 1.0), Some(poppins.clone())).with_id("button".to_string());
 
     let sublabel = Label::new("Inside a SUB Container!".to_string(), Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone()), 32.0);
-    let subbutton = Toggle::new(300.0, 60.0, "hhhhhhhh".to_string(), Color::new(0.05, 0.05, 0.1, 1.0), Color::new(1.0, 1.0, 0.5, 1.0), Some(poppins.clone()));
+    let subbutton = Toggle::new(300.0, 60.0, "hhhhhhhh".to_string(), Color::new(0.05, 0.05, 0.1, 1.0), Color::new(1.0, 1.0, 0.5, 1.0), Some(poppins.clone())).with_id("toggle".to_string());
 
     let mut subcontainer = Container::new(
         flowquad::widgets::container::Direction::Vertical,
@@ -41,9 +41,16 @@ async fn main() {
 
     loop {
         clear_background(Color::new(0.05, 0.05, 0.1, 1.0));
+        let mut input = InputState::capture();
 
-        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0);
-        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0);
+        container.layout(Constraints::loose(screen_width(), screen_height()));
+
+        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut input);
+        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut MacroquadPainter);
+
+        for event in container.take_events() {
+            println!("{:?}", event);
+        }
 
         next_frame().await;
     }
@@ -10,12 +10,13 @@ async fn main() {
 
     loop {
         clear_background(Color::new(0.05, 0.05, 0.1, 1.0));
+        let mut input = InputState::capture();
         
-        label.render(screen_width() / 2.0 - label.width() / 2.0, screen_height() / 2.0 - label.height() / 2.0 - 100.0);
-        button.update(screen_width() / 2.0 - button.width() / 2.0, screen_height() / 2.0 - button.height() / 2.0 + 100.0);
-        button.render(screen_width() / 2.0 - button.width() / 2.0, screen_height() / 2.0 - button.height() / 2.0 + 100.0);
-        toggle.update(screen_width() - toggle.width() - 10.0, 10.0);
-        toggle.render(screen_width() - toggle.width() - 10.0, 10.0);
+        label.render(screen_width() / 2.0 - label.width() / 2.0, screen_height() / 2.0 - label.height() / 2.0 - 100.0, &mut MacroquadPainter);
+        button.update(screen_width() / 2.0 - button.width() / 2.0, screen_height() / 2.0 - button.height() / 2.0 + 100.0, &mut input);
+        button.render(screen_width() / 2.0 - button.width() / 2.0, screen_height() / 2.0 - button.height() / 2.0 + 100.0, &mut MacroquadPainter);
+        toggle.update(screen_width() - toggle.width() - 10.0, 10.0, &mut input);
+        toggle.render(screen_width() - toggle.width() - 10.0, 10.0, &mut MacroquadPainter);
 
         if button.is_clicked() {
             println!("Button clicked!");
@@ -0,0 +1,30 @@
+use macroquad::prelude::*;
+use flowquad::prelude::*;
+
+#[macroquad::main("Hot Reload!")]
+async fn main() {
+    let mut watcher = ThemeWatcher::new("examples/theme.toml").unwrap();
+
+    let mut container = Container::themed(Direction::Vertical, Align::Center, 20.0);
+    container.add_child(Box::new(Label::themed("Edit examples/theme.toml!".to_string())));
+    container.add_child(Box::new(Button::themed(300.0, 60.0, "Play".to_string())));
+    container.add_child(Box::new(Button::themed(300.0, 60.0, "Quit".to_string())
+        .with_style(StyleOverride::named("danger".to_string()))));
+
+    loop {
+        match watcher.apply_changes(&mut container) {
+            Ok(true) => println!("Reloaded the theme"),
+            Ok(false) => {}
+            Err(error) => eprintln!("{}", error),
+        }
+
+        clear_background(watcher.theme().palette.background);
+        let mut input = InputState::capture();
+
+        container.layout(Constraints::loose(screen_width(), screen_height()));
+        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut input);
+        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut MacroquadPainter);
+
+        next_frame().await;
+    }
+}
@@ -14,9 +14,10 @@ async fn main() {
 
     loop {
         clear_background(Color::new(0.05, 0.05, 0.1, 1.0));
+        let mut input = InputState::capture();
         
-        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0);
-        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0);
+        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut input);
+        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut MacroquadPainter);
 
         next_frame().await;
     }
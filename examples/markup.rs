@@ -0,0 +1,28 @@
+use macroquad::prelude::*;
+use flowquad::prelude::*;
+
+#[macroquad::main("Markup!")]
+async fn main() {
+    let poppins = load_ttf_font("examples/poppins.ttf").await.unwrap();
+    let source = load_string("examples/cont.xml").await.unwrap();
+    let markup = Markup::new().with_font("poppins".to_string(), poppins);
+    let mut root = match markup.build(&source) {
+        Ok(root) => root,
+        Err(error) => panic!("examples/cont.xml: {}", error),
+    };
+
+    loop {
+        clear_background(Color::new(0.05, 0.05, 0.1, 1.0));
+        let mut input = InputState::capture();
+
+        root.layout(Constraints::loose(screen_width(), screen_height()));
+        root.update(screen_width() / 2.0 - root.width() / 2.0, screen_height() / 2.0 - root.height() / 2.0, &mut input);
+        root.render(screen_width() / 2.0 - root.width() / 2.0, screen_height() / 2.0 - root.height() / 2.0, &mut MacroquadPainter);
+
+        for event in root.take_events() {
+            println!("{:?}", event);
+        }
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,45 @@
+use macroquad::prelude::*;
+use flowquad::prelude::*;
+
+#[macroquad::main("Menus!")]
+async fn main() {
+    let poppins = load_ttf_font("examples/poppins.ttf").await.unwrap();
+    Theme::set_global(Theme::default().with_font(poppins));
+
+    let mut menu = Container::builder()
+        .align(Align::Center)
+        .gap(20.0)
+        .child(Box::new(Label::builder().text("Use the arrow keys, Enter and Escape".to_string()).font_size(24.0).build()))
+        .child(Box::new(Button::builder().size(300.0, 60.0).text("Play".to_string()).id("play".to_string()).build()))
+        .child(Box::new(Toggle::builder().size(300.0, 60.0).text("Sound".to_string()).id("sound".to_string()).build()))
+        .child(Box::new(Button::builder().size(300.0, 60.0).text("Quit".to_string()).id("quit".to_string()).build()))
+        .build();
+    let focus = FocusManager::new()
+        .with_wrap(false)
+        .with_keys(KeyMap::default()
+            .bind(KeyCode::W, Navigation::Up)
+            .bind(KeyCode::S, Navigation::Down));
+    menu.focus("play");
+
+    loop {
+        let mut input = InputState::capture();
+
+        menu.layout(Constraints::loose(screen_width(), screen_height()));
+        if focus.update(&mut menu, &mut input) == Some(Navigation::Back) {
+            menu.focus("quit");
+        }
+        menu.update(screen_width() / 2.0 - menu.width() / 2.0, screen_height() / 2.0 - menu.height() / 2.0, &mut input);
+
+        for event in menu.take_events() {
+            match event {
+                Event::Clicked { id } if id.as_deref() == Some("quit") => return,
+                event => println!("{:?}", event),
+            }
+        }
+
+        clear_background(menu.bg());
+        menu.render(screen_width() / 2.0 - menu.width() / 2.0, screen_height() / 2.0 - menu.height() / 2.0, &mut MacroquadPainter);
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,37 @@
+use macroquad::prelude::*;
+use flowquad::prelude::*;
+
+#[macroquad::main("Scrolling!")]
+async fn main() {
+    let poppins = load_ttf_font("examples/poppins.ttf").await.unwrap();
+    let mut list = Container::builder()
+        .align(Align::Center)
+        .gap(10.0)
+        .padding((10.0, 10.0, 10.0, 10.0));
+    for i in 0..30 {
+        list = list.child(Box::new(Button::builder()
+            .size(600.0, 60.0)
+            .text(format!("Button {}", i))
+            .font(poppins.clone())
+            .id(i.to_string())
+            .build()));
+    }
+    let mut scroll = ScrollView::builder(Box::new(list.build()))
+        .size(400.0, 400.0)
+        .build();
+
+    loop {
+        clear_background(Color::new(0.05, 0.05, 0.1, 1.0));
+        let mut input = InputState::capture();
+
+        scroll.layout(Constraints::loose(screen_width(), screen_height()));
+        scroll.update(screen_width() / 2.0 - scroll.width() / 2.0, screen_height() / 2.0 - scroll.height() / 2.0, &mut input);
+        scroll.render(screen_width() / 2.0 - scroll.width() / 2.0, screen_height() / 2.0 - scroll.height() / 2.0, &mut MacroquadPainter);
+
+        for event in scroll.take_events() {
+            println!("{:?}", event);
+        }
+
+        next_frame().await;
+    }
+}
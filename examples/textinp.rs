@@ -5,25 +5,34 @@ use flowquad::prelude::*;
 async fn main() {
     let poppins = load_ttf_font("examples/poppins.ttf").await.unwrap();
     let label = Label::new("Text Inputs!".to_string(), Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone()), 48.0);
-    let textinp = TextInput::new(512.0, 64.0, Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone()));
-    let textinp2 = TextInput::new(512.0, 64.0, Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone()));
+    let textinp = TextInput::new(512.0, 64.0, Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone())).with_id("first".to_string())
+        .with_placeholder("Type anything".to_string());
+    let textinp2 = TextInput::new(512.0, 64.0, Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone())).with_id("second".to_string())
+        .numeric().with_max_length(6).with_validator(|text| text.len() == 6).with_placeholder("6 digits".to_string());
+    let textarea = TextArea::new(512.0, 200.0, Color::new(0.05, 0.05, 0.1, 1.0), Color::new(0.5, 0.5, 1.0, 1.0), Some(poppins.clone()), 24.0).with_line_numbers(true);
     let mut container = Container::new(Direction::Vertical, Align::Center, 20.0, Color::new(0.05, 0.05, 0.1, 1.0), None, None);
     container.add_child(Box::new(label));
     container.add_child(Box::new(textinp));
     container.add_child(Box::new(textinp2));
+    container.add_child(Box::new(textarea));
+
+    let focus = FocusManager::new();
+    container.focus("first");
 
     let mut previous_text = String::new();
     let mut previous_text2 = String::new();
 
     loop {
         clear_background(Color::new(0.05, 0.05, 0.1, 1.0));
-        
-        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0);
-        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0);
-        // println!("Text: {}", container.get_child_as::<TextInput>(1).unwrap().get_text());
-        // println!("Text2: {}", container.get_child_as::<TextInput>(2).unwrap().get_text());
-        let textinp = container.get_child_as::<TextInput>(1).unwrap();
-        let textinp2 = container.get_child_as::<TextInput>(2).unwrap();
+        let mut input = InputState::capture();
+
+        container.layout(Constraints::loose(screen_width(), screen_height()));
+        focus.update(&mut container, &mut input);
+
+        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut input);
+        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut MacroquadPainter);
+        let textinp = container.find::<TextInput>("first").unwrap();
+        let textinp2 = container.find::<TextInput>("second").unwrap();
         if textinp.get_text() != previous_text {
             println!("Text: {}", textinp.get_text());
             previous_text = textinp.get_text();
@@ -0,0 +1,39 @@
+use macroquad::prelude::*;
+use flowquad::prelude::*;
+
+#[macroquad::main("Themes!")]
+async fn main() {
+    let poppins = load_ttf_font("examples/poppins.ttf").await.unwrap();
+    let dark = Theme::new(Palette::dark()).with_font(poppins.clone());
+    let mut light = Theme::new(Palette::light()).with_font(poppins.clone());
+    light.button.corner_radius = 12.0;
+    light.toggle.corner_radius = 12.0;
+    light.container = light.container.with_padding((20.0, 20.0, 20.0, 20.0)).with_border_width(2.0).with_corner_radius(16.0);
+    Theme::set_global(dark.clone());
+
+    let mut container = Container::themed(Direction::Vertical, Align::Center, 20.0)
+        .with_style(StyleOverride::new().padding((20.0, 20.0, 20.0, 20.0)).border_width(4.0));
+    container.add_child(Box::new(Label::themed("Pick a theme".to_string())));
+    container.add_child(Box::new(Toggle::themed(300.0, 60.0, "Light".to_string()).with_id("light".to_string())));
+    container.add_child(Box::new(TextInput::themed(300.0, 60.0).with_placeholder("Type here".to_string())));
+    container.add_child(Box::new(Button::themed(300.0, 60.0, "Danger!".to_string())
+        .with_style(StyleOverride::new().fg(RED))));
+
+    loop {
+        let mut input = InputState::capture();
+
+        container.layout(Constraints::loose(screen_width(), screen_height()));
+        container.update(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut input);
+
+        for event in container.take_events() {
+            if let Event::Toggled { on, .. } = event {
+                (&mut container as &mut dyn Widget).apply_theme(if on { &light } else { &dark });
+            }
+        }
+
+        clear_background(container.bg());
+        container.render(screen_width() / 2.0 - container.width() / 2.0, screen_height() / 2.0 - container.height() / 2.0, &mut MacroquadPainter);
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,511 @@
+//! This module defines the [`TextEditor`], the editing core shared by the text widgets.
+//!
+//! The editor knows nothing about rendering or input devices, it only keeps a string, a cursor
+//! and a selection anchor and applies edits to them. The cursor and the anchor are byte offsets
+//! which always sit on a grapheme boundary, so characters made of several code points, like
+//! accented letters or emoji, are moved over and deleted as a whole.
+//!
+//! ```
+//! use flowquad::editor::TextEditor;
+//!
+//! let mut editor = TextEditor::new();
+//! editor.insert_str("héllo 👋🏽");
+//! editor.backspace();
+//! assert_eq!(editor.text(), "héllo ");
+//!
+//! editor.move_home(false);
+//! editor.move_right(false);
+//! editor.move_right(false);
+//! editor.delete();
+//! assert_eq!(editor.text(), "hélo ");
+//! assert_eq!(editor.text_before_cursor(), "hé");
+//!
+//! editor.move_word_right(true);
+//! assert_eq!(editor.selected_text(), "lo");
+//! editor.insert_str("llo wörld");
+//! assert_eq!(editor.text(), "héllo wörld ");
+//!
+//! editor.move_word_left(false);
+//! editor.move_end(true);
+//! assert_eq!(editor.selected_text(), "wörld ");
+//!
+//! editor.undo();
+//! assert_eq!(editor.text(), "hélo ");
+//! editor.redo();
+//! assert_eq!(editor.text(), "héllo wörld ");
+//! ```
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The most undo steps a [`TextEditor`] remembers.
+const HISTORY_LIMIT: usize = 100;
+
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
+
+/// The kind of the last edit, consecutive edits of the same kind are undone in one step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+/// The text, cursor and anchor of a [`TextEditor`] before an edit.
+#[derive(Clone, Debug, PartialEq)]
+struct Snapshot {
+    text: String,
+    cursor: usize,
+    anchor: usize,
+}
+
+/// The [`TextEditor`] struct holds a string, a cursor and a selection, and edits them on grapheme
+/// boundaries.
+///
+/// The selection spans from the anchor to the cursor, it's empty when both are at the same offset.
+/// Every movement takes an `extend` flag, when it's set the anchor stays where it is so the
+/// selection grows or shrinks, otherwise the selection is collapsed.
+///
+/// Every edit is recorded so it can be undone and redone, consecutive typed characters and
+/// consecutive deletions are grouped into a single step.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextEditor {
+    text: String,
+    cursor: usize,
+    anchor: usize,
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    last_edit: Option<EditKind>,
+}
+
+impl TextEditor {
+    /// Creates a new, empty [`TextEditor`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the text being edited.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, moves the cursor to its end and clears the undo history.
+    pub fn set_text(&mut self, text: String) {
+        self.cursor = text.len();
+        self.anchor = self.cursor;
+        self.text = text;
+        self.clear_history();
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.text.clone(),
+            cursor: self.cursor,
+            anchor: self.anchor,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.text = snapshot.text;
+        self.cursor = snapshot.cursor;
+        self.anchor = snapshot.anchor;
+        self.last_edit = None;
+    }
+
+    /// Records the current state before an edit of the specified kind, unless it continues the
+    /// previous edit.
+    fn record(&mut self, kind: EditKind) {
+        if self.last_edit == Some(kind) && kind != EditKind::Other {
+            return;
+        }
+        if self.undo_stack.len() == HISTORY_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(self.snapshot());
+        self.redo_stack.clear();
+        self.last_edit = Some(kind);
+    }
+
+    /// Reverts the last edit, returns if there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(snapshot) => {
+                self.redo_stack.push(self.snapshot());
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-applies the last undone edit, returns if there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(snapshot) => {
+                self.undo_stack.push(self.snapshot());
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns if there is an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Returns if there is an undone edit to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Forgets every edit, so they can't be undone or redone anymore.
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_edit = None;
+    }
+
+    /// Returns the cursor as a byte offset into the text.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Moves the cursor to the specified byte offset, snapping it back to the closest grapheme
+    /// boundary.
+    pub fn set_cursor(&mut self, cursor: usize, extend: bool) {
+        let cursor = cursor.min(self.text.len());
+        self.cursor = if cursor == self.text.len() {
+            cursor
+        } else {
+            self.text
+                .grapheme_indices(true)
+                .map(|(i, _)| i)
+                .take_while(|i| *i <= cursor)
+                .last()
+                .unwrap_or(0)
+        };
+        self.collapse_unless(extend);
+        self.last_edit = None;
+    }
+
+    /// Returns the text in front of the cursor.
+    pub fn text_before_cursor(&self) -> &str {
+        &self.text[..self.cursor]
+    }
+
+    /// Returns the number of graphemes in the text.
+    pub fn len(&self) -> usize {
+        self.text.graphemes(true).count()
+    }
+
+    /// Returns if the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Returns the selected byte range, if anything is selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        match self.anchor.cmp(&self.cursor) {
+            std::cmp::Ordering::Less => Some(self.anchor..self.cursor),
+            std::cmp::Ordering::Greater => Some(self.cursor..self.anchor),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Returns the selected text, which is empty if nothing is selected.
+    pub fn selected_text(&self) -> &str {
+        self.selection().map_or("", |range| &self.text[range])
+    }
+
+    /// Selects the whole text.
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.cursor = self.text.len();
+    }
+
+    /// Selects the word, or the run of spaces or punctuation, around the specified byte offset.
+    pub fn select_word_at(&mut self, offset: usize) {
+        let segment = self.text
+            .split_word_bound_indices()
+            .find(|(i, w)| offset < i + w.len())
+            .or_else(|| self.text.split_word_bound_indices().next_back());
+        if let Some((start, word)) = segment {
+            self.anchor = start;
+            self.cursor = start + word.len();
+        }
+    }
+
+    fn collapse_unless(&mut self, extend: bool) {
+        if !extend {
+            self.anchor = self.cursor;
+        }
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
+
+    fn previous_word_boundary(&self) -> usize {
+        self.text
+            .split_word_bound_indices()
+            .filter(|(_, w)| is_word(w))
+            .map(|(i, _)| i)
+            .take_while(|i| *i < self.cursor)
+            .last()
+            .unwrap_or(0)
+    }
+
+    fn next_word_boundary(&self) -> usize {
+        self.text
+            .split_word_bound_indices()
+            .filter(|(_, w)| is_word(w))
+            .map(|(i, w)| i + w.len())
+            .find(|end| *end > self.cursor)
+            .unwrap_or(self.text.len())
+    }
+
+    fn remove_selection(&mut self) {
+        if let Some(range) = self.selection() {
+            self.cursor = range.start;
+            self.anchor = range.start;
+            self.text.replace_range(range, "");
+        }
+    }
+
+    /// Deletes the selected text, returns if anything was deleted.
+    pub fn delete_selection(&mut self) -> bool {
+        if self.selection().is_none() {
+            return false;
+        }
+        self.record(EditKind::Other);
+        self.remove_selection();
+        true
+    }
+
+    /// Inserts a character at the cursor, replacing the selection, and moves the cursor after it.
+    pub fn insert_char(&mut self, c: char) {
+        if self.selection().is_some() {
+            // Typing over a selection is undone together with the characters typed after it
+            self.record(EditKind::Other);
+            self.remove_selection();
+            self.last_edit = Some(EditKind::Typing);
+        } else {
+            self.record(EditKind::Typing);
+        }
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.anchor = self.cursor;
+    }
+
+    /// Inserts a string at the cursor, replacing the selection, and moves the cursor after it.
+    pub fn insert_str(&mut self, s: &str) {
+        if s.is_empty() {
+            self.delete_selection();
+            return;
+        }
+        self.record(EditKind::Other);
+        self.remove_selection();
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+        self.anchor = self.cursor;
+    }
+
+    /// Deletes the selection or the grapheme before the cursor, returns if anything was deleted.
+    pub fn backspace(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        let start = self.previous_boundary();
+        if start == self.cursor {
+            return false;
+        }
+        self.record(EditKind::Deleting);
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+        self.anchor = start;
+        true
+    }
+
+    /// Deletes the selection or the grapheme after the cursor, returns if anything was deleted.
+    pub fn delete(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        let end = self.next_boundary();
+        if end == self.cursor {
+            return false;
+        }
+        self.record(EditKind::Deleting);
+        self.text.replace_range(self.cursor..end, "");
+        true
+    }
+
+    fn move_to(&mut self, cursor: usize, extend: bool) -> bool {
+        let previous = (self.cursor, self.anchor);
+        self.cursor = cursor;
+        self.collapse_unless(extend);
+        self.last_edit = None;
+        (self.cursor, self.anchor) != previous
+    }
+
+    /// Moves the cursor one grapheme to the left, returns if anything changed.
+    ///
+    /// Without `extend`, a selection is collapsed to its start instead.
+    pub fn move_left(&mut self, extend: bool) -> bool {
+        match self.selection() {
+            Some(range) if !extend => self.move_to(range.start, false),
+            _ => self.move_to(self.previous_boundary(), extend),
+        }
+    }
+
+    /// Moves the cursor one grapheme to the right, returns if anything changed.
+    ///
+    /// Without `extend`, a selection is collapsed to its end instead.
+    pub fn move_right(&mut self, extend: bool) -> bool {
+        match self.selection() {
+            Some(range) if !extend => self.move_to(range.end, false),
+            _ => self.move_to(self.next_boundary(), extend),
+        }
+    }
+
+    /// Moves the cursor to the start of the previous word, returns if anything changed.
+    pub fn move_word_left(&mut self, extend: bool) -> bool {
+        self.move_to(self.previous_word_boundary(), extend)
+    }
+
+    /// Moves the cursor to the end of the next word, returns if anything changed.
+    pub fn move_word_right(&mut self, extend: bool) -> bool {
+        self.move_to(self.next_word_boundary(), extend)
+    }
+
+    /// Moves the cursor to the start of the text, returns if anything changed.
+    pub fn move_home(&mut self, extend: bool) -> bool {
+        self.move_to(0, extend)
+    }
+
+    /// Moves the cursor to the end of the text, returns if anything changed.
+    pub fn move_end(&mut self, extend: bool) -> bool {
+        self.move_to(self.text.len(), extend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> TextEditor {
+        let mut editor = TextEditor::new();
+        editor.set_text(text.to_string());
+        editor
+    }
+
+    #[test]
+    fn backspace_removes_combining_marks_with_their_letter() {
+        let mut editor = editor("cafe\u{301}");
+        assert!(editor.backspace());
+        assert_eq!(editor.text(), "caf");
+        assert_eq!(editor.cursor(), 3);
+    }
+
+    #[test]
+    fn delete_removes_combining_marks_with_their_letter() {
+        let mut editor = editor("e\u{301}\u{302}x");
+        editor.move_home(false);
+        assert!(editor.delete());
+        assert_eq!(editor.text(), "x");
+        assert_eq!(editor.cursor(), 0);
+    }
+
+    #[test]
+    fn backspace_and_delete_remove_zwj_emoji_as_a_whole() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let mut editor = editor(&format!("a{family}b{family}"));
+        assert!(editor.backspace());
+        assert_eq!(editor.text(), format!("a{family}b"));
+
+        editor.move_home(false);
+        editor.move_right(false);
+        assert!(editor.delete());
+        assert_eq!(editor.text(), "ab");
+        assert_eq!(editor.cursor(), 1);
+    }
+
+    #[test]
+    fn set_cursor_snaps_back_to_a_grapheme_boundary() {
+        let thumbs = "\u{1F44D}\u{1F3FD}";
+        let mut editor = editor(&format!("e\u{301}{thumbs}!"));
+        editor.set_cursor(1, false);
+        assert_eq!(editor.cursor(), 0);
+
+        editor.set_cursor(3 + 5, false);
+        assert_eq!(editor.cursor(), 3);
+
+        editor.set_cursor(3 + thumbs.len(), false);
+        assert_eq!(editor.cursor(), 3 + thumbs.len());
+
+        editor.set_cursor(usize::MAX, false);
+        assert_eq!(editor.cursor(), editor.text().len());
+        assert_eq!(editor.selection(), None);
+    }
+
+    #[test]
+    fn set_cursor_extends_the_selection() {
+        let mut editor = editor("héllo");
+        editor.set_cursor(0, false);
+        editor.set_cursor(2, true);
+        assert_eq!(editor.selected_text(), "h");
+    }
+
+    #[test]
+    fn select_word_at_the_end_selects_the_last_word() {
+        let mut editor = editor("hello world");
+        editor.select_word_at(editor.text().len());
+        assert_eq!(editor.selected_text(), "world");
+
+        editor.select_word_at(usize::MAX);
+        assert_eq!(editor.selected_text(), "world");
+    }
+
+    #[test]
+    fn select_word_at_in_empty_text_selects_nothing() {
+        let mut editor = editor("");
+        editor.select_word_at(0);
+        assert_eq!(editor.selection(), None);
+    }
+
+    #[test]
+    fn word_movement_skips_punctuation() {
+        let mut editor = editor("foo, (bar)!");
+        editor.move_home(false);
+        assert!(editor.move_word_right(false));
+        assert_eq!(editor.text_before_cursor(), "foo");
+        assert!(editor.move_word_right(false));
+        assert_eq!(editor.text_before_cursor(), "foo, (bar");
+        assert!(editor.move_word_right(false));
+        assert_eq!(editor.cursor(), editor.text().len());
+        assert!(!editor.move_word_right(false));
+
+        assert!(editor.move_word_left(false));
+        assert_eq!(editor.text_before_cursor(), "foo, (");
+        assert!(editor.move_word_left(true));
+        assert_eq!(editor.cursor(), 0);
+        assert_eq!(editor.selected_text(), "foo, (");
+        assert!(editor.move_word_left(false));
+        assert_eq!(editor.selection(), None);
+        assert!(!editor.move_word_left(false));
+    }
+}
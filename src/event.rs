@@ -0,0 +1,33 @@
+//! This module defines the [`Event`]s emitted by interactive widgets.
+//!
+//! Widgets queue their events during [`Widget::update`](crate::widgets::widget::Widget::update),
+//! and the app drains them afterwards with
+//! [`Container::take_events`](crate::widgets::container::Container::take_events). Events which
+//! aren't drained are dropped on the next update.
+
+/// The [`Event`] enum defines everything an interactive widget can report to the app. Every event
+/// carries the ID of the widget which emitted it, if it has one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A [`Button`](crate::widgets::button::Button) was clicked.
+    Clicked { id: Option<String> },
+    /// A [`Toggle`](crate::widgets::toggle::Toggle) was switched on or off.
+    Toggled { id: Option<String>, on: bool },
+    /// The text of a [`TextInput`](crate::widgets::textinput::TextInput) or a
+    /// [`TextArea`](crate::widgets::textarea::TextArea) was edited.
+    TextChanged { id: Option<String>, text: String },
+    /// Enter was pressed in a [`TextInput`](crate::widgets::textinput::TextInput).
+    Submitted { id: Option<String>, text: String },
+}
+
+impl Event {
+    /// Returns the ID of the widget which emitted the event.
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Event::Clicked { id }
+            | Event::Toggled { id, .. }
+            | Event::TextChanged { id, .. }
+            | Event::Submitted { id, .. } => id.as_deref(),
+        }
+    }
+}
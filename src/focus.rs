@@ -0,0 +1,354 @@
+//! This module defines how the keyboard focus moves between widgets, so a UI can be used without
+//! a mouse.
+//!
+//! Only one widget in a tree has the focus at a time. Text widgets take it when they're clicked
+//! and type into it, while a focused [`Button`](crate::widgets::button::Button) or
+//! [`Toggle`](crate::widgets::toggle::Toggle) is clicked by the confirm action. The focused widget
+//! draws a ring around itself in the accent color of the [`Theme`](crate::theme::Theme).
+//!
+//! This module contains the following:
+//! 1. The [`Navigation`] enum, which defines the actions that move the focus or use the focused
+//!    widget.
+//! 2. The [`KeyMap`] struct, which binds keys to [`Navigation`] actions.
+//! 3. The [`FocusManager`] struct, which moves the focus through a widget tree.
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::input::InputState;
+use crate::widgets::widget::{focusable_descendants, Widget};
+
+/// How much being off to the side counts against a widget, compared to being further away, when
+/// the focus moves by direction.
+const SIDEWAYS_PENALTY: f32 = 2.0;
+
+/// The [`Navigation`] enum defines the actions a [`FocusManager`] performs, which are read from the
+/// keyboard through a [`KeyMap`] or passed to [`FocusManager::navigate`] from a gamepad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Navigation {
+    /// Moves the focus to the next widget in tree order.
+    Next,
+    /// Moves the focus to the previous widget in tree order.
+    Previous,
+    /// Moves the focus to the closest widget above.
+    Up,
+    /// Moves the focus to the closest widget below.
+    Down,
+    /// Moves the focus to the closest widget on the left.
+    Left,
+    /// Moves the focus to the closest widget on the right.
+    Right,
+    /// Clicks the focused widget.
+    Confirm,
+    /// Leaves the tree as it is, it's up to the app what it means, like closing a menu.
+    Back,
+}
+
+impl Navigation {
+    /// Returns the direction the action moves the focus in, if it moves it by direction.
+    fn direction(self) -> Option<Vec2> {
+        match self {
+            Navigation::Up => Some(vec2(0.0, -1.0)),
+            Navigation::Down => Some(vec2(0.0, 1.0)),
+            Navigation::Left => Some(vec2(-1.0, 0.0)),
+            Navigation::Right => Some(vec2(1.0, 0.0)),
+            _ => None,
+        }
+    }
+}
+
+/// The [`KeyMap`] struct binds keys to the [`Navigation`] actions of a [`FocusManager`].
+///
+/// By default Tab moves to the next widget, and to the previous one while Shift is held, the arrow
+/// keys move by direction, Enter and Space confirm and Escape goes back. While a text widget has
+/// the focus only the next and previous actions are read, the other keys are left for editing.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let keys = KeyMap::default()
+///     .bind(KeyCode::W, Navigation::Up)
+///     .bind(KeyCode::S, Navigation::Down)
+///     .unbind(KeyCode::Space);
+/// assert_eq!(keys.navigation(KeyCode::W), Some(Navigation::Up));
+/// assert_eq!(keys.navigation(KeyCode::Up), Some(Navigation::Up));
+/// assert_eq!(keys.navigation(KeyCode::Space), None);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyMap {
+    bindings: HashMap<KeyCode, Navigation>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::new()
+            .bind(KeyCode::Tab, Navigation::Next)
+            .bind(KeyCode::Up, Navigation::Up)
+            .bind(KeyCode::Down, Navigation::Down)
+            .bind(KeyCode::Left, Navigation::Left)
+            .bind(KeyCode::Right, Navigation::Right)
+            .bind(KeyCode::Enter, Navigation::Confirm)
+            .bind(KeyCode::KpEnter, Navigation::Confirm)
+            .bind(KeyCode::Space, Navigation::Confirm)
+            .bind(KeyCode::Escape, Navigation::Back)
+    }
+}
+
+impl KeyMap {
+    /// Creates a new, empty [`KeyMap`]. See [`KeyMap::default`] for the usual bindings.
+    pub fn new() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Binds `key` to the specified [`Navigation`] action, replacing what it was bound to.
+    pub fn bind(mut self, key: KeyCode, navigation: Navigation) -> Self {
+        self.bindings.insert(key, navigation);
+        self
+    }
+
+    /// Removes the binding of `key`.
+    pub fn unbind(mut self, key: KeyCode) -> Self {
+        self.bindings.remove(&key);
+        self
+    }
+
+    /// Returns the [`Navigation`] action `key` is bound to.
+    pub fn navigation(&self, key: KeyCode) -> Option<Navigation> {
+        self.bindings.get(&key).copied()
+    }
+
+    /// Returns the action of the keys pressed this frame, leaving out everything but the next and
+    /// previous actions while `editing_text`. Shift turns the next action into the previous one.
+    fn pressed(&self, input: &InputState, editing_text: bool) -> Option<Navigation> {
+        let navigation = input.keys_pressed.iter()
+            .filter_map(|key| self.navigation(*key))
+            .filter(|navigation| !editing_text || matches!(navigation, Navigation::Next | Navigation::Previous))
+            .min()?;
+        if navigation == Navigation::Next && input.is_shift_down() {
+            Some(Navigation::Previous)
+        } else {
+            Some(navigation)
+        }
+    }
+}
+
+/// The [`FocusManager`] struct moves the keyboard focus through the widgets of a tree which can
+/// take it, and clicks the focused one, following the [`Navigation`] actions bound in its
+/// [`KeyMap`].
+///
+/// [`FocusManager::update`] is meant to be called once per frame, before the tree is updated. The
+/// focus moves by direction to the closest widget that way, measured between their centers where
+/// they were during the last update. Actions from other devices, like a gamepad, are performed
+/// with [`FocusManager::navigate`]. The focus can also be moved from code with
+/// [`focus`](crate::widgets::widget::Widget#method.focus), and cleared with
+/// [`clear_focus`](crate::widgets::widget::Widget#method.clear_focus).
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut menu = Container::builder()
+///     .child(Box::new(Button::builder().id("play".to_string()).build()))
+///     .child(Box::new(Toggle::builder().id("sound".to_string()).build()))
+///     .child(Box::new(Button::builder().id("quit".to_string()).build()))
+///     .build();
+/// let focus = FocusManager::new();
+/// let mut input = ScriptedInput::new();
+/// input.tap_key(KeyCode::Tab).tap_key(KeyCode::Tab).tap_key(KeyCode::Space);
+///
+/// let mut events = Vec::new();
+/// menu.layout(Constraints::unbounded());
+/// while !input.is_finished() {
+///     let mut frame = input.poll();
+///     focus.update(&mut menu, &mut frame);
+///     menu.update(0.0, 0.0, &mut frame);
+///     events.append(&mut menu.take_events());
+/// }
+/// assert_eq!(menu.focused_widget().and_then(|w| w.id()), Some("sound"));
+/// assert_eq!(events, vec![Event::Toggled { id: Some("sound".to_string()), on: true }]);
+///
+/// menu.focus("quit");
+/// assert!(menu.find::<Button>("quit").unwrap().is_focused());
+/// assert!(!menu.find::<Toggle>("sound").unwrap().is_focused());
+/// ```
+///
+/// A game menu can be driven with the arrow keys alone.
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let button = |id: &str| Box::new(Button::builder().size(100.0, 40.0).id(id.to_string()).build());
+/// let mut menu = Grid::builder()
+///     .columns(vec![Track::Fixed(100.0), Track::Fixed(100.0)])
+///     .gap(10.0, 10.0)
+///     .child(button("new"), GridCell::new(0, 0))
+///     .child(button("load"), GridCell::new(1, 0))
+///     .child(button("options"), GridCell::new(0, 1))
+///     .child(button("quit"), GridCell::new(1, 1))
+///     .build();
+/// let focus = FocusManager::new();
+/// let mut input = ScriptedInput::new();
+/// input.tap_key(KeyCode::Down).tap_key(KeyCode::Right).tap_key(KeyCode::Down)
+///     .tap_key(KeyCode::Left).tap_key(KeyCode::Enter).tap_key(KeyCode::Escape);
+///
+/// let (mut events, mut actions) = (Vec::new(), Vec::new());
+/// menu.layout(Constraints::unbounded());
+/// while !input.is_finished() {
+///     let mut frame = input.poll();
+///     actions.extend(focus.update(&mut menu, &mut frame));
+///     menu.update(0.0, 0.0, &mut frame);
+///     events.append(&mut (&mut menu as &mut dyn Widget).take_events());
+/// }
+/// assert_eq!(events, vec![Event::Clicked { id: Some("options".to_string()) }]);
+/// assert_eq!(actions.last(), Some(&Navigation::Back));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FocusManager {
+    keys: KeyMap,
+    wrap: bool,
+}
+
+impl Default for FocusManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FocusManager {
+    /// Creates a new [`FocusManager`] with the default [`KeyMap`], which wraps around from the
+    /// last widget to the first.
+    pub fn new() -> Self {
+        Self {
+            keys: KeyMap::default(),
+            wrap: true,
+        }
+    }
+
+    /// Sets the [`KeyMap`] the actions are read from.
+    pub fn with_keys(mut self, keys: KeyMap) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Sets if the focus wraps around from the last widget to the first and back, otherwise it
+    /// stops at either end.
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Returns the [`KeyMap`] the actions are read from.
+    pub fn keys(&self) -> &KeyMap {
+        &self.keys
+    }
+
+    /// Performs the [`Navigation`] action of the keys pressed this frame on `root`, and returns
+    /// it, so the app can act on [`Navigation::Back`]. The keyboard is only marked as taken if
+    /// the action did something, or was [`Navigation::Back`].
+    pub fn update(&self, root: &mut dyn Widget, input: &mut InputState) -> Option<Navigation> {
+        let editing_text = root.focused_widget().is_some_and(|widget| widget.edits_text());
+        let navigation = self.keys.pressed(input, editing_text)?;
+        if self.navigate(root, navigation) || navigation == Navigation::Back {
+            input.consume_keyboard();
+        }
+        Some(navigation)
+    }
+
+    /// Performs the specified [`Navigation`] action on `root`. Returns if the focus moved or a
+    /// widget was clicked.
+    pub fn navigate(&self, root: &mut dyn Widget, navigation: Navigation) -> bool {
+        match navigation {
+            Navigation::Next => self.focus_next(root),
+            Navigation::Previous => self.focus_previous(root),
+            Navigation::Confirm => {
+                let mut widgets = Vec::new();
+                focusable_descendants(root, &mut widgets);
+                match widgets.into_iter().find(|widget| widget.is_focused()) {
+                    Some(widget) => {
+                        widget.activate();
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Navigation::Back => false,
+            _ => self.move_focus_towards(root, navigation.direction().unwrap_or_default()),
+        }
+    }
+
+    /// Moves the focus to the next widget in `root` which can take it, or to the first one if none
+    /// has it. Returns if the focus moved.
+    pub fn focus_next(&self, root: &mut dyn Widget) -> bool {
+        self.move_focus(root, true)
+    }
+
+    /// Moves the focus to the previous widget in `root` which can take it, or to the last one if
+    /// none has it. Returns if the focus moved.
+    pub fn focus_previous(&self, root: &mut dyn Widget) -> bool {
+        self.move_focus(root, false)
+    }
+
+    fn move_focus(&self, root: &mut dyn Widget, forward: bool) -> bool {
+        let mut widgets = Vec::new();
+        focusable_descendants(root, &mut widgets);
+        if widgets.is_empty() {
+            return false;
+        }
+
+        let count = widgets.len();
+        let current = widgets.iter().position(|widget| widget.is_focused());
+        let next = match (current, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) if i + 1 < count => i + 1,
+            (Some(i), false) if i > 0 => i - 1,
+            (Some(_), true) if self.wrap => 0,
+            (Some(_), false) if self.wrap => count - 1,
+            (Some(_), _) => return false,
+        };
+        if current == Some(next) {
+            return false;
+        }
+
+        for (i, widget) in widgets.into_iter().enumerate() {
+            widget.set_focused(i == next);
+        }
+        true
+    }
+
+    /// Moves the focus to the closest widget in `direction` from the focused one, or to the first
+    /// one if none has it. Returns if the focus moved.
+    fn move_focus_towards(&self, root: &mut dyn Widget, direction: Vec2) -> bool {
+        let mut widgets = Vec::new();
+        focusable_descendants(root, &mut widgets);
+        let Some(current) = widgets.iter().position(|widget| widget.is_focused()) else {
+            return self.focus_next(root);
+        };
+        let Some(from) = widgets[current].screen_rect().map(|rect| rect.center()) else {
+            return false;
+        };
+
+        let next = widgets.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != current)
+            .filter_map(|(i, widget)| {
+                let offset = widget.screen_rect()?.center() - from;
+                let ahead = offset.dot(direction);
+                let sideways = offset.perp_dot(direction).abs();
+                (ahead > 0.0).then_some((i, ahead + sideways * SIDEWAYS_PENALTY))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i);
+        let Some(next) = next else {
+            return false;
+        };
+
+        for (i, widget) in widgets.into_iter().enumerate() {
+            widget.set_focused(i == next);
+        }
+        true
+    }
+}
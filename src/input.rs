@@ -0,0 +1,478 @@
+//! This module defines how widgets receive mouse and keyboard input.
+//!
+//! This module contains the following:
+//! 1. The [`InputState`] struct, a snapshot of the input for a single frame which is passed into
+//!    [`Widget::update`](crate::widgets::widget::Widget::update), and which keeps track of the
+//!    input the widgets took.
+//! 2. The [`InputSource`] trait, which produces an [`InputState`] every frame.
+//! 3. The [`MacroquadInput`] struct, the default [`InputSource`] which reads from macroquad.
+//! 4. The [`ScriptedInput`] struct, an in-memory [`InputSource`] which replays a recorded sequence
+//!    of frames, so widgets can be driven without a window.
+//! 5. The [`Clipboard`] trait, which gives widgets access to the clipboard, and its
+//!    [`MacroquadClipboard`] and [`MemoryClipboard`] implementations.
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use macroquad::prelude::*;
+
+/// The longest time in seconds between two clicks for them to count as a double click.
+pub(crate) const DOUBLE_CLICK_TIME: f64 = 0.4;
+
+/// The [`Clipboard`] trait which defines how widgets read and write the clipboard.
+pub trait Clipboard: Debug {
+    /// Returns the text in the clipboard, if there is any.
+    fn get(&self) -> Option<String>;
+    /// Replaces the text in the clipboard.
+    fn set(&self, text: &str);
+}
+
+/// The [`MacroquadClipboard`] struct is the default [`Clipboard`], it uses the system clipboard
+/// through macroquad.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MacroquadClipboard;
+
+impl Clipboard for MacroquadClipboard {
+    fn get(&self) -> Option<String> {
+        macroquad::miniquad::window::clipboard_get()
+    }
+
+    fn set(&self, text: &str) {
+        macroquad::miniquad::window::clipboard_set(text);
+    }
+}
+
+/// The [`MemoryClipboard`] struct is a [`Clipboard`] which only keeps its text in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryClipboard {
+    text: RefCell<Option<String>>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get(&self) -> Option<String> {
+        self.text.borrow().clone()
+    }
+
+    fn set(&self, text: &str) {
+        *self.text.borrow_mut() = Some(text.to_string());
+    }
+}
+
+/// The [`InputState`] struct is a snapshot of the mouse and keyboard for a single frame.
+///
+/// Widgets are updated from the top down, in the reverse of the order they're drawn, and the
+/// interactive ones and the panels with a background take the mouse while it's over them, so a
+/// click only reaches the topmost widget. Afterwards the game can check
+/// [`InputState::ui_wants_mouse`] and [`InputState::ui_wants_keyboard`] before using the input
+/// itself.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut stack = Stack::builder()
+///     .child(Box::new(Button::builder().size(200.0, 100.0).id("below".to_string()).build()), StackPosition::new(Anchor::TopLeft))
+///     .child(Box::new(Button::builder().size(100.0, 50.0).id("above".to_string()).build()), StackPosition::new(Anchor::TopLeft))
+///     .build();
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(300.0, 20.0).move_mouse(20.0, 20.0).click(MouseButton::Left);
+///
+/// let mut frame = input.poll();
+/// stack.update(0.0, 0.0, &mut frame);
+/// assert!(!frame.ui_wants_mouse());
+///
+/// while !input.is_finished() {
+///     frame = input.poll();
+///     stack.update(0.0, 0.0, &mut frame);
+/// }
+/// assert!(frame.ui_wants_mouse());
+/// assert_eq!((&mut stack as &mut dyn Widget).take_events(), vec![Event::Clicked { id: Some("above".to_string()) }]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct InputState {
+    /// The time in seconds since the app started.
+    pub time: f64,
+    /// The position of the mouse cursor.
+    pub mouse_position: (f32, f32),
+    /// The mouse wheel movement during this frame.
+    pub mouse_wheel: (f32, f32),
+    /// The mouse buttons which are currently held down.
+    pub mouse_down: HashSet<MouseButton>,
+    /// The mouse buttons which were pressed this frame.
+    pub mouse_pressed: HashSet<MouseButton>,
+    /// The mouse buttons which were released this frame.
+    pub mouse_released: HashSet<MouseButton>,
+    /// The keys which are currently held down.
+    pub keys_down: HashSet<KeyCode>,
+    /// The keys which were pressed this frame.
+    pub keys_pressed: HashSet<KeyCode>,
+    /// The characters typed this frame, in order.
+    pub chars: Vec<char>,
+    /// The clipboard widgets copy to and paste from.
+    pub clipboard: Rc<dyn Clipboard>,
+    /// If a widget took the mouse this frame, so the widgets below it and the game leave it alone.
+    pub mouse_consumed: bool,
+    /// If a widget took the keyboard this frame, so other widgets and the game leave it alone.
+    pub keyboard_consumed: bool,
+    /// The rectangles the mouse is restricted to, innermost last, see
+    /// [`InputState::push_mouse_clip`].
+    pub mouse_clip: Vec<Rect>,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            mouse_position: (0.0, 0.0),
+            mouse_wheel: (0.0, 0.0),
+            mouse_down: HashSet::new(),
+            mouse_pressed: HashSet::new(),
+            mouse_released: HashSet::new(),
+            keys_down: HashSet::new(),
+            keys_pressed: HashSet::new(),
+            chars: Vec::new(),
+            clipboard: Rc::new(MemoryClipboard::default()),
+            mouse_consumed: false,
+            keyboard_consumed: false,
+            mouse_clip: Vec::new(),
+        }
+    }
+}
+
+impl InputState {
+    /// Captures the current frame's input from macroquad.
+    ///
+    /// This is a shorthand for polling a [`MacroquadInput`].
+    pub fn capture() -> Self {
+        MacroquadInput.poll()
+    }
+
+    /// Returns the position of the mouse cursor.
+    pub fn mouse_position(&self) -> (f32, f32) {
+        self.mouse_position
+    }
+
+    /// Returns the mouse wheel movement during this frame.
+    pub fn mouse_wheel(&self) -> (f32, f32) {
+        self.mouse_wheel
+    }
+
+    /// Returns if the mouse cursor is inside the specified rectangle.
+    pub fn is_mouse_over(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        let (mx, my) = self.mouse_position;
+        mx >= x && mx <= x + width && my >= y && my <= y + height
+    }
+
+    /// Returns if the mouse cursor is inside the specified rectangle and the current mouse clip,
+    /// and no widget above it took the mouse this frame. Widgets are updated from the top down, so
+    /// the first one which takes the mouse with [`InputState::consume_mouse`] hides it from the
+    /// ones it covers.
+    pub fn hit_test(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        let (mx, my) = self.mouse_position;
+        let clipped = self.mouse_clip.last().is_some_and(|clip| !clip.contains(vec2(mx, my)));
+        !self.mouse_consumed && !clipped && self.is_mouse_over(x, y, width, height)
+    }
+
+    /// Restricts [`InputState::hit_test`] to the part of the specified rectangle inside the
+    /// current mouse clip, until the matching [`InputState::pop_mouse_clip`]. Widgets which only
+    /// show part of their children, like a [`ScrollView`](crate::widgets::scrollview::ScrollView),
+    /// use it so the hidden parts can't be clicked.
+    ///
+    /// ```
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut input = InputState::default();
+    /// input.mouse_position = (150.0, 20.0);
+    /// assert!(input.hit_test(100.0, 0.0, 100.0, 40.0));
+    ///
+    /// input.push_mouse_clip(0.0, 0.0, 120.0, 100.0);
+    /// assert!(!input.hit_test(100.0, 0.0, 100.0, 40.0));
+    /// input.pop_mouse_clip();
+    /// assert!(input.hit_test(100.0, 0.0, 100.0, 40.0));
+    /// ```
+    pub fn push_mouse_clip(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let rect = Rect::new(x, y, width.max(0.0), height.max(0.0));
+        let clip = match self.mouse_clip.last() {
+            Some(outer) => outer.intersect(rect).unwrap_or(Rect::new(x, y, 0.0, 0.0)),
+            None => rect,
+        };
+        self.mouse_clip.push(clip);
+    }
+
+    /// Restores the mouse clip from before the last [`InputState::push_mouse_clip`].
+    pub fn pop_mouse_clip(&mut self) {
+        self.mouse_clip.pop();
+    }
+
+    /// Marks the mouse as taken by a widget for the rest of the frame.
+    pub fn consume_mouse(&mut self) {
+        self.mouse_consumed = true;
+    }
+
+    /// Marks the keyboard as taken by a widget for the rest of the frame.
+    pub fn consume_keyboard(&mut self) {
+        self.keyboard_consumed = true;
+    }
+
+    /// Takes the mouse wheel movement of this frame, leaving none for other widgets.
+    pub fn take_mouse_wheel(&mut self) -> (f32, f32) {
+        std::mem::take(&mut self.mouse_wheel)
+    }
+
+    /// Returns if the UI took the mouse this frame, so the game should ignore it. It's meant to
+    /// be asked after the UI is updated.
+    pub fn ui_wants_mouse(&self) -> bool {
+        self.mouse_consumed
+    }
+
+    /// Returns if the UI took the keyboard this frame, like while a text widget has the focus, so
+    /// the game should ignore it. It's meant to be asked after the UI is updated.
+    pub fn ui_wants_keyboard(&self) -> bool {
+        self.keyboard_consumed
+    }
+
+    /// Returns if the specified mouse button is held down.
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse_down.contains(&button)
+    }
+
+    /// Returns if the specified mouse button was pressed this frame.
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_pressed.contains(&button)
+    }
+
+    /// Returns if the specified mouse button was released this frame.
+    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        self.mouse_released.contains(&button)
+    }
+
+    /// Returns if the specified key is held down.
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    /// Returns if the specified key was pressed this frame.
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Returns if either Shift key is held down.
+    pub fn is_shift_down(&self) -> bool {
+        self.is_key_down(KeyCode::LeftShift) || self.is_key_down(KeyCode::RightShift)
+    }
+
+    /// Returns if either Control key, or either Command key on macOS, is held down.
+    pub fn is_ctrl_down(&self) -> bool {
+        self.is_key_down(KeyCode::LeftControl) || self.is_key_down(KeyCode::RightControl)
+            || self.is_key_down(KeyCode::LeftSuper) || self.is_key_down(KeyCode::RightSuper)
+    }
+
+    /// Takes the characters typed this frame, leaving none for other widgets.
+    pub fn take_chars(&mut self) -> Vec<char> {
+        std::mem::take(&mut self.chars)
+    }
+}
+
+/// The [`InputSource`] trait which produces an [`InputState`] every frame.
+pub trait InputSource {
+    /// Returns the input for the next frame.
+    fn poll(&mut self) -> InputState;
+}
+
+/// The [`MacroquadInput`] struct is the default [`InputSource`], it reads the input straight from
+/// macroquad.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MacroquadInput;
+
+impl InputSource for MacroquadInput {
+    fn poll(&mut self) -> InputState {
+        let buttons = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+        let mut chars = Vec::new();
+        while let Some(c) = get_char_pressed() {
+            chars.push(c);
+        }
+
+        InputState {
+            time: get_time(),
+            mouse_position: mouse_position(),
+            mouse_wheel: mouse_wheel(),
+            mouse_down: buttons.into_iter().filter(|b| is_mouse_button_down(*b)).collect(),
+            mouse_pressed: buttons.into_iter().filter(|b| is_mouse_button_pressed(*b)).collect(),
+            mouse_released: buttons.into_iter().filter(|b| is_mouse_button_released(*b)).collect(),
+            keys_down: get_keys_down(),
+            keys_pressed: get_keys_pressed(),
+            chars,
+            clipboard: Rc::new(MacroquadClipboard),
+            mouse_consumed: false,
+            keyboard_consumed: false,
+            mouse_clip: Vec::new(),
+        }
+    }
+}
+
+/// A single frame recorded into a [`ScriptedInput`].
+#[derive(Clone, Debug, Default)]
+struct ScriptedFrame {
+    mouse_position: (f32, f32),
+    mouse_wheel: (f32, f32),
+    mouse_down: HashSet<MouseButton>,
+    keys_down: HashSet<KeyCode>,
+    chars: Vec<char>,
+}
+
+/// The [`ScriptedInput`] struct is an in-memory [`InputSource`] which replays a sequence of frames.
+///
+/// Every method that records input appends a new frame which keeps the mouse position and the held
+/// buttons and keys of the previous one, pressed and released states are worked out when the frames
+/// are polled. Frames are 1/60th of a second apart, and all of them share a [`MemoryClipboard`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut button = Button::new(100.0, 40.0, "Click".to_string(), BLACK, WHITE, None);
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(50.0, 20.0).click(MouseButton::Left);
+///
+/// let mut clicked = false;
+/// while !input.is_finished() {
+///     button.update(0.0, 0.0, &mut input.poll());
+///     clicked |= button.is_clicked();
+/// }
+/// assert!(clicked);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedInput {
+    frames: VecDeque<ScriptedFrame>,
+    last: ScriptedFrame,
+    polled: ScriptedFrame,
+    polled_count: u32,
+    clipboard: Rc<MemoryClipboard>,
+}
+
+impl ScriptedInput {
+    /// Creates a new, empty [`ScriptedInput`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the text in the clipboard shared by the frames.
+    pub fn set_clipboard(&mut self, text: &str) -> &mut Self {
+        self.clipboard.set(text);
+        self
+    }
+
+    /// Returns the text in the clipboard shared by the frames.
+    pub fn clipboard(&self) -> Option<String> {
+        self.clipboard.get()
+    }
+
+    /// Returns if every recorded frame has been polled.
+    pub fn is_finished(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    fn push(&mut self, edit: impl FnOnce(&mut ScriptedFrame)) -> &mut Self {
+        let mut frame = ScriptedFrame {
+            mouse_wheel: (0.0, 0.0),
+            chars: Vec::new(),
+            ..self.last.clone()
+        };
+        edit(&mut frame);
+        self.last = frame.clone();
+        self.frames.push_back(frame);
+        self
+    }
+
+    /// Records the specified number of frames where nothing changes.
+    pub fn wait(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.push(|_| {});
+        }
+        self
+    }
+
+    /// Records a frame where the mouse moves to the specified position.
+    pub fn move_mouse(&mut self, x: f32, y: f32) -> &mut Self {
+        self.push(|f| f.mouse_position = (x, y))
+    }
+
+    /// Records a frame where the mouse wheel is scrolled.
+    pub fn scroll(&mut self, x: f32, y: f32) -> &mut Self {
+        self.push(|f| f.mouse_wheel = (x, y))
+    }
+
+    /// Records a frame where the specified mouse button is pressed.
+    pub fn press_mouse(&mut self, button: MouseButton) -> &mut Self {
+        self.push(|f| {
+            f.mouse_down.insert(button);
+        })
+    }
+
+    /// Records a frame where the specified mouse button is released.
+    pub fn release_mouse(&mut self, button: MouseButton) -> &mut Self {
+        self.push(|f| {
+            f.mouse_down.remove(&button);
+        })
+    }
+
+    /// Records a press and a release of the specified mouse button.
+    pub fn click(&mut self, button: MouseButton) -> &mut Self {
+        self.press_mouse(button).release_mouse(button)
+    }
+
+    /// Records a frame where the specified key is pressed.
+    pub fn press_key(&mut self, key: KeyCode) -> &mut Self {
+        self.push(|f| {
+            f.keys_down.insert(key);
+        })
+    }
+
+    /// Records a frame where the specified key is released.
+    pub fn release_key(&mut self, key: KeyCode) -> &mut Self {
+        self.push(|f| {
+            f.keys_down.remove(&key);
+        })
+    }
+
+    /// Records a press and a release of the specified key.
+    pub fn tap_key(&mut self, key: KeyCode) -> &mut Self {
+        self.press_key(key).release_key(key)
+    }
+
+    /// Records a frame where the specified text is typed.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        self.push(|f| f.chars = text.chars().collect())
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn poll(&mut self) -> InputState {
+        let previous = std::mem::take(&mut self.polled);
+        let frame = self.frames.pop_front().unwrap_or_else(|| ScriptedFrame {
+            mouse_wheel: (0.0, 0.0),
+            chars: Vec::new(),
+            ..previous.clone()
+        });
+
+        let state = InputState {
+            time: self.polled_count as f64 / 60.0,
+            mouse_position: frame.mouse_position,
+            mouse_wheel: frame.mouse_wheel,
+            mouse_down: frame.mouse_down.clone(),
+            mouse_pressed: frame.mouse_down.difference(&previous.mouse_down).copied().collect(),
+            mouse_released: previous.mouse_down.difference(&frame.mouse_down).copied().collect(),
+            keys_down: frame.keys_down.clone(),
+            keys_pressed: frame.keys_down.difference(&previous.keys_down).copied().collect(),
+            chars: frame.chars.clone(),
+            clipboard: self.clipboard.clone(),
+            mouse_consumed: false,
+            keyboard_consumed: false,
+            mouse_clip: Vec::new(),
+        };
+        self.polled = frame;
+        self.polled_count += 1;
+        state
+    }
+}
@@ -0,0 +1,133 @@
+//! This module defines the types used by the layout pass.
+//!
+//! Every frame, the app calls [`Widget::layout`](crate::widgets::widget::Widget::layout) on its
+//! root widget with the [`Constraints`] it has to fit in. Containers pass constraints down to
+//! their children, size themselves from the [`Size`]s they get back and cache where each child
+//! goes, so [`Widget::update`](crate::widgets::widget::Widget::update) and
+//! [`Widget::render`](crate::widgets::widget::Widget::render) don't have to work it out again.
+//!
+//! ```
+//! use macroquad::prelude::*;
+//! use flowquad::prelude::*;
+//!
+//! let mut container = Container::new(Direction::Vertical, Align::Center, 10.0, BLACK, Some((5.0, 5.0, 5.0, 5.0)), None);
+//! container.add_child(Box::new(Button::new(100.0, 40.0, "Play".to_string(), BLACK, WHITE, None)));
+//! container.add_child(Box::new(Button::new(60.0, 40.0, "Quit".to_string(), BLACK, WHITE, None)));
+//!
+//! let size = container.layout(Constraints::unbounded());
+//! assert_eq!(size, Size::new(110.0, 100.0));
+//! assert_eq!(container.child_rect(1), Some(Rect::new(25.0, 55.0, 60.0, 40.0)));
+//!
+//! // A minimum size makes the container grow, and its children are aligned in the extra space
+//! let size = container.layout(Constraints::new(200.0, f32::INFINITY, 0.0, f32::INFINITY));
+//! assert_eq!(size, Size::new(200.0, 100.0));
+//! assert_eq!(container.child_rect(1), Some(Rect::new(70.0, 55.0, 60.0, 40.0)));
+//! ```
+use crate::widgets::widget::Widget;
+
+/// The [`Size`] struct holds the width and height a widget takes up.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Size {
+    /// Creates a new [`Size`].
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// The [`Constraints`] struct holds the smallest and largest size a widget may take up during
+/// layout. The maximums may be infinite, the minimums may not.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Constraints {
+    pub min_width: f32,
+    pub max_width: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+}
+
+impl Constraints {
+    /// Creates new [`Constraints`].
+    pub fn new(min_width: f32, max_width: f32, min_height: f32, max_height: f32) -> Self {
+        Self {
+            min_width,
+            max_width,
+            min_height,
+            max_height,
+        }
+    }
+
+    /// Creates [`Constraints`] which let a widget take up any size.
+    pub fn unbounded() -> Self {
+        Self::new(0.0, f32::INFINITY, 0.0, f32::INFINITY)
+    }
+
+    /// Creates [`Constraints`] which let a widget take up any size up to `width` and `height`.
+    pub fn loose(width: f32, height: f32) -> Self {
+        Self::new(0.0, width, 0.0, height)
+    }
+
+    /// Creates [`Constraints`] which force a widget to take up exactly `width` and `height`.
+    pub fn tight(width: f32, height: f32) -> Self {
+        Self::new(width, width, height, height)
+    }
+
+    /// Returns the same [`Constraints`] without the minimums.
+    pub fn loosen(&self) -> Self {
+        Self::new(0.0, self.max_width, 0.0, self.max_height)
+    }
+
+    /// Returns the [`Constraints`] left for the content of a widget after taking away `horizontal`
+    /// and `vertical` space, like padding.
+    pub fn shrink(&self, horizontal: f32, vertical: f32) -> Self {
+        Self::new(
+            (self.min_width - horizontal).max(0.0),
+            (self.max_width - horizontal).max(0.0),
+            (self.min_height - vertical).max(0.0),
+            (self.max_height - vertical).max(0.0),
+        )
+    }
+
+    /// Returns these [`Constraints`] clamped into `outer`, so they satisfy both where they can and
+    /// `outer` wins where they can't.
+    pub fn enforce(&self, outer: Constraints) -> Self {
+        Self::new(
+            self.min_width.clamp(outer.min_width, outer.max_width),
+            self.max_width.clamp(outer.min_width, outer.max_width),
+            self.min_height.clamp(outer.min_height, outer.max_height),
+            self.max_height.clamp(outer.min_height, outer.max_height),
+        )
+    }
+
+    /// Returns the [`Size`] closest to `size` which satisfies the [`Constraints`].
+    pub fn constrain(&self, size: Size) -> Size {
+        Size::new(
+            size.width.min(self.max_width).max(self.min_width),
+            size.height.min(self.max_height).max(self.min_height),
+        )
+    }
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Self::unbounded()
+    }
+}
+
+/// Returns the current size of every widget in `children`, without laying them out.
+pub(crate) fn intrinsic_sizes(children: &[Box<dyn Widget>]) -> Vec<Size> {
+    children.iter().map(|child| Size::new(child.width(), child.height())).collect()
+}
+
+/// Lays out `widget` at its natural size unless it was laid out since its last update, for apps
+/// which don't lay out their widgets themselves. `laid_out` returns the flag the widget's layout
+/// sets, which is cleared for the next frame.
+pub(crate) fn layout_unless_laid_out<W: Widget>(widget: &mut W, laid_out: fn(&mut W) -> &mut bool) {
+    if !*laid_out(widget) {
+        widget.layout(Constraints::unbounded());
+    }
+    *laid_out(widget) = false;
+}
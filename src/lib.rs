@@ -4,7 +4,7 @@
 //!
 //!
 //! This is an example which shows a label and an image.
-//! ```
+//! ```no_run
 //! use macroquad::prelude::*;
 //! use flowquad::prelude::*;
 //!
@@ -17,17 +17,28 @@
 //!
 //!     loop {
 //!         clear_background(Color::new(0.05, 0.05, 0.1, 1.0));
+//!         let mut input = InputState::capture();
 //!
 //!         // `Label::update` Does nothing but kept here for consistency
-//!         label.update(screen_width() / 2.0 - label.width() / 2.0, screen_height() / 2.0 - label.height() / 2.0 - 100.0);
-//!         label.render(screen_width() / 2.0 - label.width() / 2.0, screen_height() / 2.0 - label.height() / 2.0 - 100.0);
-//!         image.update(screen_width() / 2.0 - image.width() / 2.0, screen_height() / 2.0 - image.height() / 2.0 + 50.0);
-//!         image.render(screen_width() / 2.0 - image.width() / 2.0, screen_height() / 2.0 - image.height() / 2.0 + 50.0);
+//!         label.update(screen_width() / 2.0 - label.width() / 2.0, screen_height() / 2.0 - label.height() / 2.0 - 100.0, &mut input);
+//!         label.render(screen_width() / 2.0 - label.width() / 2.0, screen_height() / 2.0 - label.height() / 2.0 - 100.0, &mut MacroquadPainter);
+//!         image.update(screen_width() / 2.0 - image.width() / 2.0, screen_height() / 2.0 - image.height() / 2.0 + 50.0, &mut input);
+//!         image.render(screen_width() / 2.0 - image.width() / 2.0, screen_height() / 2.0 - image.height() / 2.0 + 50.0, &mut MacroquadPainter);
 //!
 //!         next_frame().await;
 //!     }
 //! }
 //! ```
 
+pub mod editor;
+pub mod event;
+pub mod focus;
+pub mod input;
+pub mod layout;
+pub mod markup;
+pub mod painter;
 pub mod prelude;
+pub mod theme;
+#[cfg(feature = "serde")]
+pub mod themefile;
 pub mod widgets;
@@ -0,0 +1,671 @@
+//! This module defines a markup format which describes a widget tree, so it doesn't have to be
+//! built by hand.
+//!
+//! The markup looks like XML. Every element is a widget and its attributes set it up, containers
+//! hold their children as nested elements, and the text of labels, buttons, toggles, text inputs
+//! and text areas can be written between their tags. Comments are written `<!-- like this -->`,
+//! and `&lt;`, `&gt;`, `&amp;`, `&quot;` and `&apos;` can be used in text and attribute values.
+//!
+//! The elements are `container`, `label`, `button`, `toggle`, `textinput`, `textarea` and `image`.
+//! Every element takes an `id`, a named `style` from the [`Theme`](crate::theme::Theme), and
+//! `bg`, `fg`, `font`, `font-size`, `border-width`, `corner-radius` and `padding` which override
+//! its style. Children of a container also take `grow` and `shrink`, see
+//! [`Flex`](crate::widgets::container::Flex). Then:
+//! - `container` takes `direction` (`horizontal`, `vertical`, `horizontal-wrap` or
+//!   `vertical-wrap`), `align` (`start`, `center`, `end` or `stretch`), `justify` (`start`,
+//!   `center`, `end`, `space-between`, `space-around` or `space-evenly`), `gap`, `line-gap`,
+//!   `width` and `height`.
+//! - `label` takes `text`.
+//! - `button` and `toggle` take `width`, `height` and `text`.
+//! - `textinput` takes `width`, `height`, `text`, `placeholder`, `password`, `numeric` and
+//!   `max-length`.
+//! - `textarea` takes `width`, `height`, `text` and `line-numbers`.
+//! - `image` takes `width`, `height` and `texture`.
+//!
+//! Colors are written `#rrggbb` or `#rrggbbaa`, paddings are either one number or four for the
+//! left, right, top and bottom. Fonts and textures are referred to by the names they're
+//! registered with on the [`Markup`].
+//!
+//! This module contains the following:
+//! 1. The [`Markup`] struct, which builds widget trees from markup.
+//! 2. The [`MarkupError`] struct, which says what's wrong with some markup and where.
+//!
+//! ```
+//! use macroquad::prelude::*;
+//! use flowquad::prelude::*;
+//!
+//! let mut root = Markup::new().build(r##"
+//!     <container id="menu" direction="vertical" align="center" gap="10" padding="5">
+//!         <label>Main menu</label>
+//!         <button id="play" width="100" height="40">Play</button>
+//!         <button id="quit" width="100" height="40" style="danger" fg="#ff0000">Quit</button>
+//!     </container>
+//! "##).unwrap();
+//!
+//! assert!(root.find::<Button>("play").is_some());
+//! let menu = root.as_any().downcast_ref::<Container>().unwrap();
+//! assert_eq!(menu.len(), 3);
+//!
+//! let error = Markup::new().build("<container>\n    <buton/>\n</container>").err().unwrap();
+//! assert_eq!((error.line, error.column), (2, 5));
+//! assert_eq!(error.to_string(), "line 2, column 5: unknown element <buton>");
+//! ```
+use std::collections::HashMap;
+use std::fmt;
+
+use macroquad::prelude::*;
+
+use crate::theme::{parse_hex_color, StyleOverride};
+use crate::widgets::button::Button;
+use crate::widgets::container::{Align, Container, Direction, Flex, Justify};
+use crate::widgets::image::Image;
+use crate::widgets::label::Label;
+use crate::widgets::textarea::TextArea;
+use crate::widgets::textinput::TextInput;
+use crate::widgets::toggle::Toggle;
+use crate::widgets::widget::Widget;
+
+/// The attributes every element takes.
+const COMMON_ATTRIBUTES: [&str; 9] = ["id", "style", "bg", "fg", "font", "font-size", "border-width", "corner-radius", "padding"];
+
+/// The [`MarkupError`] struct says what's wrong with some markup, and the line and column it's
+/// at, both counted from 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkupError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl MarkupError {
+    fn new(position: Position, message: String) -> Self {
+        Self {
+            line: position.line,
+            column: position.column,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+struct Attribute {
+    name: String,
+    value: String,
+    position: Position,
+}
+
+impl Attribute {
+    fn error<T>(&self, expected: &str) -> Result<T, MarkupError> {
+        Err(MarkupError::new(self.position, format!("expected {} for \"{}\", found \"{}\"", expected, self.name, self.value)))
+    }
+
+    fn number(&self) -> Result<f32, MarkupError> {
+        match self.value.trim().parse::<f32>() {
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => self.error("a number"),
+        }
+    }
+
+    fn count(&self) -> Result<usize, MarkupError> {
+        self.value.trim().parse::<usize>().or_else(|_| self.error("a whole number"))
+    }
+
+    fn boolean(&self) -> Result<bool, MarkupError> {
+        match self.value.trim() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => self.error("true or false"),
+        }
+    }
+
+    fn color(&self) -> Result<Color, MarkupError> {
+        parse_hex_color(self.value.trim()).map_or_else(|| self.error("a color like #rrggbb"), Ok)
+    }
+
+    fn padding(&self) -> Result<(f32, f32, f32, f32), MarkupError> {
+        let numbers = self.value.split_whitespace().map(str::parse::<f32>).collect::<Result<Vec<_>, _>>();
+        match numbers.as_deref() {
+            Ok(&[padding]) => Ok((padding, padding, padding, padding)),
+            Ok(&[left, right, top, bottom]) => Ok((left, right, top, bottom)),
+            _ => self.error("one or four numbers"),
+        }
+    }
+
+    /// Parses the value as one of the specified keywords.
+    fn keyword<T: Copy>(&self, keywords: &[(&str, T)]) -> Result<T, MarkupError> {
+        match keywords.iter().find(|(keyword, _)| *keyword == self.value.trim()) {
+            Some((_, value)) => Ok(*value),
+            None => {
+                let names = keywords.iter().map(|(keyword, _)| *keyword).collect::<Vec<_>>();
+                self.error(&format!("one of {}", names.join(", ")))
+            }
+        }
+    }
+}
+
+struct Element {
+    name: String,
+    position: Position,
+    attributes: Vec<Attribute>,
+    children: Vec<Element>,
+    text: String,
+    text_position: Position,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
+    }
+
+    fn required(&self, name: &str) -> Result<&Attribute, MarkupError> {
+        self.attribute(name)
+            .ok_or_else(|| MarkupError::new(self.position, format!("<{}> needs a \"{}\" attribute", self.name, name)))
+    }
+
+    /// Fails on the first attribute which isn't common or in `allowed`.
+    fn check_attributes(&self, allowed: &[&str], flex: bool) -> Result<(), MarkupError> {
+        for attribute in &self.attributes {
+            let name = attribute.name.as_str();
+            let known = COMMON_ATTRIBUTES.contains(&name)
+                || allowed.contains(&name)
+                || (flex && (name == "grow" || name == "shrink"));
+            if !known {
+                return Err(MarkupError::new(attribute.position, format!("<{}> has no attribute \"{}\"", self.name, name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the text of the element, from its `text` attribute or from between its tags.
+    fn text(&self) -> Result<String, MarkupError> {
+        match self.attribute("text") {
+            Some(attribute) if !self.text.is_empty() => {
+                Err(MarkupError::new(attribute.position, format!("<{}> has both a \"text\" attribute and text inside it", self.name)))
+            }
+            Some(attribute) => Ok(attribute.value.clone()),
+            None => Ok(self.text.clone()),
+        }
+    }
+
+    fn leaf(&self) -> Result<(), MarkupError> {
+        match self.children.first() {
+            Some(child) => Err(MarkupError::new(child.position, format!("<{}> can't have children", self.name))),
+            None => Ok(()),
+        }
+    }
+
+    fn size(&self) -> Result<(f32, f32), MarkupError> {
+        Ok((self.required("width")?.number()?, self.required("height")?.number()?))
+    }
+}
+
+/// Reads markup into a tree of [`Element`]s.
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    position: Position,
+}
+
+impl Parser {
+    fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            index: 0,
+            position: Position { line: 1, column: 1 },
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(c)
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, c)| self.chars.get(self.index + i) == Some(&c))
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, MarkupError> {
+        Err(MarkupError::new(self.position, message))
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, MarkupError> {
+        match self.peek() {
+            Some(c) => self.error(format!("expected {}, found '{}'", expected, c)),
+            None => self.error(format!("expected {}, found the end of the markup", expected)),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), MarkupError> {
+        if self.peek() == Some(c) {
+            self.bump();
+            Ok(())
+        } else {
+            self.unexpected(&format!("'{}'", c))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), MarkupError> {
+        let start = self.position;
+        while !self.starts_with("-->") {
+            if self.bump().is_none() {
+                return Err(MarkupError::new(start, "unclosed comment".to_string()));
+            }
+        }
+        for _ in 0..3 {
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), MarkupError> {
+        loop {
+            self.skip_whitespace();
+            if !self.starts_with("<!--") {
+                return Ok(());
+            }
+            self.skip_comment()?;
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_') {
+            name.push(c);
+            self.bump();
+        }
+        name
+    }
+
+    fn entity(&mut self) -> Result<char, MarkupError> {
+        let start = self.position;
+        self.bump();
+        let name = self.name();
+        if self.peek() != Some(';') {
+            return Err(MarkupError::new(start, "expected an entity like &amp;".to_string()));
+        }
+        self.bump();
+        match name.as_str() {
+            "lt" => Ok('<'),
+            "gt" => Ok('>'),
+            "amp" => Ok('&'),
+            "quot" => Ok('"'),
+            "apos" => Ok('\''),
+            _ => Err(MarkupError::new(start, format!("unknown entity &{};", name))),
+        }
+    }
+
+    fn document(&mut self) -> Result<Element, MarkupError> {
+        self.skip_whitespace_and_comments()?;
+        if self.peek() != Some('<') {
+            return self.unexpected("an element");
+        }
+        let root = self.element()?;
+        self.skip_whitespace_and_comments()?;
+        if self.peek().is_some() {
+            return self.error("expected the end of the markup after the root element".to_string());
+        }
+        Ok(root)
+    }
+
+    fn attribute(&mut self) -> Result<Attribute, MarkupError> {
+        let position = self.position;
+        let name = self.name();
+        if name.is_empty() {
+            return self.unexpected("an attribute name");
+        }
+        self.skip_whitespace();
+        self.expect('=')?;
+        self.skip_whitespace();
+        let quote = match self.peek() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return self.unexpected("a quoted value"),
+        };
+        self.bump();
+
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                Some(c) if c == quote => break,
+                Some('&') => value.push(self.entity()?),
+                Some('<') | None => return self.unexpected(&format!("{} to close the value", quote)),
+                Some(c) => {
+                    value.push(c);
+                    self.bump();
+                }
+            }
+        }
+        self.bump();
+        Ok(Attribute { name, value, position })
+    }
+
+    fn element(&mut self) -> Result<Element, MarkupError> {
+        let position = self.position;
+        self.expect('<')?;
+        let name = self.name();
+        if name.is_empty() {
+            return self.unexpected("an element name");
+        }
+        let mut element = Element {
+            name,
+            position,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+            text_position: position,
+        };
+
+        loop {
+            let had_whitespace = self.peek().is_some_and(char::is_whitespace);
+            self.skip_whitespace();
+            match self.peek() {
+                Some('/') => {
+                    self.bump();
+                    self.expect('>')?;
+                    return Ok(element);
+                }
+                Some('>') => {
+                    self.bump();
+                    break;
+                }
+                Some(_) if had_whitespace => {
+                    let attribute = self.attribute()?;
+                    if element.attribute(&attribute.name).is_some() {
+                        return Err(MarkupError::new(attribute.position, format!("\"{}\" is set twice", attribute.name)));
+                    }
+                    element.attributes.push(attribute);
+                }
+                _ => return self.unexpected("'>', '/>' or an attribute"),
+            }
+        }
+
+        let mut text = String::new();
+        loop {
+            if self.starts_with("<!--") {
+                self.skip_comment()?;
+            } else if self.starts_with("</") {
+                let close = self.position;
+                self.bump();
+                self.bump();
+                let name = self.name();
+                if name != element.name {
+                    return Err(MarkupError::new(close, format!("expected </{}>, found </{}>", element.name, name)));
+                }
+                self.skip_whitespace();
+                self.expect('>')?;
+                break;
+            } else {
+                match self.peek() {
+                    Some('<') => element.children.push(self.element()?),
+                    Some('&') => {
+                        if text.trim().is_empty() {
+                            element.text_position = self.position;
+                        }
+                        text.push(self.entity()?);
+                    }
+                    Some(c) => {
+                        if text.trim().is_empty() && !c.is_whitespace() {
+                            element.text_position = self.position;
+                        }
+                        text.push(c);
+                        self.bump();
+                    }
+                    None => return Err(MarkupError::new(position, format!("<{}> is never closed", element.name))),
+                }
+            }
+        }
+        element.text = text.trim().to_string();
+        Ok(element)
+    }
+}
+
+/// The [`Markup`] struct builds widget trees from markup, see the [module](crate::markup) for
+/// the format. Fonts and textures have to be registered on it before markup can use them.
+#[derive(Default)]
+pub struct Markup {
+    fonts: HashMap<String, Font>,
+    textures: HashMap<String, Texture2D>,
+}
+
+impl Markup {
+    /// Creates a new [`Markup`] without any fonts or textures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a font which markup can use with `font="name"`.
+    pub fn with_font(mut self, name: String, font: Font) -> Self {
+        self.fonts.insert(name, font);
+        self
+    }
+
+    /// Registers a texture which images in markup can show with `texture="name"`.
+    pub fn with_texture(mut self, name: String, texture: Texture2D) -> Self {
+        self.textures.insert(name, texture);
+        self
+    }
+
+    /// Builds the widget tree described by `source`, or returns the first thing wrong with it.
+    pub fn build(&self, source: &str) -> Result<Box<dyn Widget>, MarkupError> {
+        let root = Parser::new(source).document()?;
+        self.widget(&root, false)
+    }
+
+    /// Returns the style overrides set by the attributes of an element.
+    fn style(&self, element: &Element) -> Result<StyleOverride, MarkupError> {
+        let mut style = StyleOverride::new();
+        if let Some(attribute) = element.attribute("style") {
+            style.name = Some(attribute.value.clone());
+        }
+        if let Some(attribute) = element.attribute("bg") {
+            style.bg = Some(attribute.color()?);
+        }
+        if let Some(attribute) = element.attribute("fg") {
+            style.fg = Some(attribute.color()?);
+        }
+        if let Some(attribute) = element.attribute("font") {
+            let font = self.fonts.get(&attribute.value)
+                .ok_or_else(|| MarkupError::new(attribute.position, format!("no font is called \"{}\"", attribute.value)))?;
+            style.font = Some(font.clone());
+        }
+        if let Some(attribute) = element.attribute("font-size") {
+            style.font_size = Some(attribute.number()?);
+        }
+        if let Some(attribute) = element.attribute("border-width") {
+            style.border_width = Some(attribute.number()?);
+        }
+        if let Some(attribute) = element.attribute("corner-radius") {
+            style.corner_radius = Some(attribute.number()?);
+        }
+        if let Some(attribute) = element.attribute("padding") {
+            style.padding = Some(attribute.padding()?);
+        }
+        Ok(style)
+    }
+
+    /// Builds the widget for an element, `flex` is whether it's the child of a container.
+    fn widget(&self, element: &Element, flex: bool) -> Result<Box<dyn Widget>, MarkupError> {
+        let id = element.attribute("id").map(|attribute| attribute.value.clone());
+        let style = self.style(element)?;
+
+        let widget: Box<dyn Widget> = match element.name.as_str() {
+            "container" => {
+                element.check_attributes(&["direction", "align", "justify", "gap", "line-gap", "width", "height"], flex)?;
+                return self.container(element, style, id);
+            }
+            "label" => {
+                element.check_attributes(&["text"], flex)?;
+                element.leaf()?;
+                let mut label = Label::themed(element.text()?).with_style(style);
+                if let Some(id) = id {
+                    label = label.with_id(id);
+                }
+                Box::new(label)
+            }
+            "button" => {
+                element.check_attributes(&["width", "height", "text"], flex)?;
+                element.leaf()?;
+                let (width, height) = element.size()?;
+                let mut button = Button::themed(width, height, element.text()?).with_style(style);
+                if let Some(id) = id {
+                    button = button.with_id(id);
+                }
+                Box::new(button)
+            }
+            "toggle" => {
+                element.check_attributes(&["width", "height", "text"], flex)?;
+                element.leaf()?;
+                let (width, height) = element.size()?;
+                let mut toggle = Toggle::themed(width, height, element.text()?).with_style(style);
+                if let Some(id) = id {
+                    toggle = toggle.with_id(id);
+                }
+                Box::new(toggle)
+            }
+            "textinput" => {
+                element.check_attributes(&["width", "height", "text", "placeholder", "password", "numeric", "max-length"], flex)?;
+                element.leaf()?;
+                let (width, height) = element.size()?;
+                let mut input = TextInput::themed(width, height).with_style(style);
+                if let Some(id) = id {
+                    input = input.with_id(id);
+                }
+                if let Some(attribute) = element.attribute("placeholder") {
+                    input = input.with_placeholder(attribute.value.clone());
+                }
+                if let Some(attribute) = element.attribute("password") {
+                    input = input.with_password(attribute.boolean()?);
+                }
+                if let Some(attribute) = element.attribute("numeric") && attribute.boolean()? {
+                    input = input.numeric();
+                }
+                if let Some(attribute) = element.attribute("max-length") {
+                    input = input.with_max_length(attribute.count()?);
+                }
+                input.set_text(element.text()?);
+                Box::new(input)
+            }
+            "textarea" => {
+                element.check_attributes(&["width", "height", "text", "line-numbers"], flex)?;
+                element.leaf()?;
+                let (width, height) = element.size()?;
+                let mut textarea = TextArea::themed(width, height).with_style(style);
+                if let Some(id) = id {
+                    textarea = textarea.with_id(id);
+                }
+                if let Some(attribute) = element.attribute("line-numbers") {
+                    textarea = textarea.with_line_numbers(attribute.boolean()?);
+                }
+                textarea.set_text(element.text()?);
+                Box::new(textarea)
+            }
+            "image" => {
+                element.check_attributes(&["width", "height", "texture"], flex)?;
+                element.leaf()?;
+                self.no_text(element)?;
+                let (width, height) = element.size()?;
+                let attribute = element.required("texture")?;
+                let texture = self.textures.get(&attribute.value)
+                    .ok_or_else(|| MarkupError::new(attribute.position, format!("no texture is called \"{}\"", attribute.value)))?;
+                let mut image = Image::new(height, width, texture.clone());
+                if let Some(id) = id {
+                    image = image.with_id(id);
+                }
+                Box::new(image)
+            }
+            name => return Err(MarkupError::new(element.position, format!("unknown element <{}>", name))),
+        };
+        Ok(widget)
+    }
+
+    fn no_text(&self, element: &Element) -> Result<(), MarkupError> {
+        if element.text.is_empty() {
+            Ok(())
+        } else {
+            Err(MarkupError::new(element.text_position, format!("<{}> can't have text inside it", element.name)))
+        }
+    }
+
+    fn container(&self, element: &Element, style: StyleOverride, id: Option<String>) -> Result<Box<dyn Widget>, MarkupError> {
+        self.no_text(element)?;
+        let direction = match element.attribute("direction") {
+            Some(attribute) => attribute.keyword(&[
+                ("horizontal", Direction::Horizontal),
+                ("vertical", Direction::Vertical),
+                ("horizontal-wrap", Direction::HorizontalWrap),
+                ("vertical-wrap", Direction::VerticalWrap),
+            ])?,
+            None => Direction::Vertical,
+        };
+        let align = match element.attribute("align") {
+            Some(attribute) => attribute.keyword(&[
+                ("start", Align::Start),
+                ("center", Align::Center),
+                ("end", Align::End),
+                ("stretch", Align::Stretch),
+            ])?,
+            None => Align::Start,
+        };
+        let gap = element.attribute("gap").map(Attribute::number).transpose()?.unwrap_or(0.0);
+
+        let mut container = Container::themed(direction, align, gap).with_style(style);
+        if let Some(id) = id {
+            container = container.with_id(id);
+        }
+        if let Some(attribute) = element.attribute("justify") {
+            container = container.with_justify(attribute.keyword(&[
+                ("start", Justify::Start),
+                ("center", Justify::Center),
+                ("end", Justify::End),
+                ("space-between", Justify::SpaceBetween),
+                ("space-around", Justify::SpaceAround),
+                ("space-evenly", Justify::SpaceEvenly),
+            ])?);
+        }
+        if let Some(attribute) = element.attribute("line-gap") {
+            container = container.with_line_gap(attribute.number()?);
+        }
+        if let Some(attribute) = element.attribute("width") {
+            container = container.with_width(attribute.number()?);
+        }
+        if let Some(attribute) = element.attribute("height") {
+            container = container.with_height(attribute.number()?);
+        }
+
+        for child in &element.children {
+            let grow = child.attribute("grow").map(Attribute::number).transpose()?.unwrap_or(0.0);
+            let shrink = child.attribute("shrink").map(Attribute::number).transpose()?.unwrap_or(0.0);
+            container.add_flex_child(self.widget(child, true)?, Flex::new(grow, shrink));
+        }
+        Ok(Box::new(container))
+    }
+}
@@ -0,0 +1,293 @@
+//! This module defines how widgets draw themselves.
+//!
+//! This module contains the following:
+//! 1. The [`TextMeasure`] trait, which widgets measure their text through during layout.
+//! 2. The [`Painter`] trait, which every
+//!    [`Widget::render`](crate::widgets::widget::Widget::render) draws through.
+//! 3. The [`MacroquadPainter`] struct, the default [`Painter`] which draws with macroquad.
+//! 4. The [`RecordingPainter`] struct, a [`Painter`] which records a list of [`DrawCommand`]s
+//!    instead of drawing, so layouts can be checked without a window or a GPU.
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+
+/// The [`TextMeasure`] trait which measures text without drawing it.
+///
+/// Layout happens outside of rendering, so widgets which size themselves to their text measure it
+/// through the global [`TextMeasure`], which is a [`MacroquadPainter`] unless it's replaced with
+/// [`set_text_measure`].
+pub trait TextMeasure {
+    /// Measures the specified text.
+    fn measure_text(&self, text: &str, font: Option<&Font>, font_size: u16) -> TextDimensions;
+}
+
+/// The [`Painter`] trait which defines the drawing primitives widgets are rendered with.
+pub trait Painter: TextMeasure {
+    /// Draws a filled rectangle.
+    fn draw_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color);
+    /// Draws the outline of a rectangle.
+    fn draw_rectangle_lines(&mut self, x: f32, y: f32, width: f32, height: f32, thickness: f32, color: Color);
+    /// Draws a filled rectangle with its corners rounded by `radius`.
+    fn draw_rounded_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, color: Color);
+    /// Draws the outline of a rectangle with its corners rounded by `radius`.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_rounded_rectangle_lines(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, thickness: f32, color: Color);
+    /// Draws a line between two points.
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color);
+    /// Draws text with its baseline at the specified position.
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, font: Option<&Font>, font_size: u16, color: Color);
+    /// Draws a texture stretched over the specified rectangle.
+    fn draw_texture(&mut self, texture: &Texture2D, x: f32, y: f32, width: f32, height: f32, color: Color);
+    /// Restricts drawing to the part of the specified rectangle inside the current clip
+    /// rectangle, until the matching [`Painter::pop_clip`].
+    fn push_clip(&mut self, x: f32, y: f32, width: f32, height: f32);
+    /// Restores the clip rectangle from before the last [`Painter::push_clip`].
+    fn pop_clip(&mut self);
+}
+
+thread_local! {
+    /// The clip rectangles pushed on the [`MacroquadPainter`]. It lives next to macroquad's own
+    /// global state since a new painter is usually made for every render.
+    static CLIP_STACK: RefCell<Vec<Rect>> = const { RefCell::new(Vec::new()) };
+    /// The [`TextMeasure`] used during layout.
+    static TEXT_MEASURE: RefCell<Box<dyn TextMeasure>> = RefCell::new(Box::new(MacroquadPainter));
+}
+
+/// Replaces the global [`TextMeasure`] widgets measure their text with during layout.
+///
+/// macroquad can only measure text once its window is open, so layouts with text in them are
+/// checked without a window by measuring with a [`RecordingPainter`] instead.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// set_text_measure(RecordingPainter::new());
+///
+/// let mut container = Container::new(Direction::Vertical, Align::Start, 0.0, BLACK, Some((5.0, 5.0, 5.0, 5.0)), None);
+/// container.add_child(Box::new(Label::new("Score".to_string(), BLACK, WHITE, None, 20.0)));
+///
+/// assert_eq!(container.layout(Constraints::unbounded()), Size::new(60.0, 30.0));
+/// ```
+pub fn set_text_measure(measure: impl TextMeasure + 'static) {
+    TEXT_MEASURE.set(Box::new(measure));
+}
+
+/// Calls `f` with the global [`TextMeasure`].
+pub(crate) fn with_text_measure<R>(f: impl FnOnce(&dyn TextMeasure) -> R) -> R {
+    TEXT_MEASURE.with_borrow(|measure| f(measure.as_ref()))
+}
+
+/// Sets macroquad's scissor rectangle, which is in physical pixels.
+fn apply_scissor(clip: Option<Rect>) {
+    let scale = screen_dpi_scale();
+    let clip = clip.map(|rect| (
+        (rect.x * scale) as i32,
+        (rect.y * scale) as i32,
+        (rect.w * scale) as i32,
+        (rect.h * scale) as i32,
+    ));
+    // SAFETY: macroquad is single threaded and the context isn't borrowed anywhere else while a
+    // widget renders
+    unsafe { get_internal_gl() }.quad_gl.scissor(clip);
+}
+
+/// Returns the center of every corner circle of a rounded rectangle, with the angle its quarter
+/// starts at, going clockwise from the top left.
+fn corner_arcs(x: f32, y: f32, width: f32, height: f32, radius: f32) -> [(f32, f32, f32); 4] {
+    [
+        (x + radius, y + radius, 180.0),
+        (x + width - radius, y + radius, 270.0),
+        (x + width - radius, y + height - radius, 0.0),
+        (x + radius, y + height - radius, 90.0),
+    ]
+}
+
+/// The [`MacroquadPainter`] struct is the default [`Painter`], it draws straight into macroquad.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MacroquadPainter;
+
+impl TextMeasure for MacroquadPainter {
+    fn measure_text(&self, text: &str, font: Option<&Font>, font_size: u16) -> TextDimensions {
+        measure_text(text, font, font_size, 1.0)
+    }
+}
+
+impl Painter for MacroquadPainter {
+    fn draw_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        draw_rectangle(x, y, width, height, color);
+    }
+
+    fn draw_rectangle_lines(&mut self, x: f32, y: f32, width: f32, height: f32, thickness: f32, color: Color) {
+        draw_rectangle_lines(x, y, width, height, thickness, color);
+    }
+
+    fn draw_rounded_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, color: Color) {
+        let radius = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+        // The pieces don't overlap, so translucent colors blend evenly
+        draw_rectangle(x + radius, y, width - radius * 2.0, height, color);
+        draw_rectangle(x, y + radius, radius, height - radius * 2.0, color);
+        draw_rectangle(x + width - radius, y + radius, radius, height - radius * 2.0, color);
+        for (center_x, center_y, rotation) in corner_arcs(x, y, width, height, radius) {
+            draw_arc(center_x, center_y, 32, 0.0, rotation, radius, 90.0, color);
+        }
+    }
+
+    fn draw_rounded_rectangle_lines(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, thickness: f32, color: Color) {
+        let radius = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+        // Like `draw_rectangle_lines`, half the thickness is drawn inside the rectangle
+        let inset = (thickness / 2.0).min(radius);
+        draw_rectangle(x + radius, y, width - radius * 2.0, inset, color);
+        draw_rectangle(x + radius, y + height - inset, width - radius * 2.0, inset, color);
+        draw_rectangle(x, y + radius, inset, height - radius * 2.0, color);
+        draw_rectangle(x + width - inset, y + radius, inset, height - radius * 2.0, color);
+        for (center_x, center_y, rotation) in corner_arcs(x, y, width, height, radius) {
+            draw_arc(center_x, center_y, 32, radius - inset, rotation, inset, 90.0, color);
+        }
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        draw_line(x1, y1, x2, y2, thickness, color);
+    }
+
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, font: Option<&Font>, font_size: u16, color: Color) {
+        draw_text_ex(text, x, y, TextParams {
+            font,
+            font_size,
+            font_scale: 1.0,
+            color,
+            ..Default::default()
+        });
+    }
+
+    fn draw_texture(&mut self, texture: &Texture2D, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        draw_texture_ex(texture, x, y, color, DrawTextureParams {
+            dest_size: Some(vec2(width, height)),
+            ..Default::default()
+        });
+    }
+
+    fn push_clip(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let clip = CLIP_STACK.with_borrow_mut(|stack| {
+            let rect = Rect::new(x, y, width.max(0.0), height.max(0.0));
+            let clip = match stack.last() {
+                Some(outer) => outer.intersect(rect).unwrap_or(Rect::new(x, y, 0.0, 0.0)),
+                None => rect,
+            };
+            stack.push(clip);
+            clip
+        });
+        apply_scissor(Some(clip));
+    }
+
+    fn pop_clip(&mut self) {
+        let clip = CLIP_STACK.with_borrow_mut(|stack| {
+            stack.pop();
+            stack.last().copied()
+        });
+        apply_scissor(clip);
+    }
+}
+
+/// A single drawing primitive recorded by a [`RecordingPainter`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    Rectangle { x: f32, y: f32, width: f32, height: f32, color: Color },
+    RectangleLines { x: f32, y: f32, width: f32, height: f32, thickness: f32, color: Color },
+    RoundedRectangle { x: f32, y: f32, width: f32, height: f32, radius: f32, color: Color },
+    RoundedRectangleLines { x: f32, y: f32, width: f32, height: f32, radius: f32, thickness: f32, color: Color },
+    Line { x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color },
+    Text { text: String, x: f32, y: f32, font_size: u16, color: Color },
+    Texture { texture: Texture2D, x: f32, y: f32, width: f32, height: f32, color: Color },
+    PushClip { x: f32, y: f32, width: f32, height: f32 },
+    PopClip,
+}
+
+/// The [`RecordingPainter`] struct is a [`Painter`] which records every primitive as a
+/// [`DrawCommand`] instead of drawing it.
+///
+/// Since fonts can't be loaded without a window, text is measured as if every character was half
+/// as wide as the font size, which is good enough to compare layouts against each other.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let button = Button::new(100.0, 40.0, "Click".to_string(), BLACK, WHITE, None);
+/// let mut painter = RecordingPainter::new();
+/// button.render(10.0, 10.0, &mut painter);
+///
+/// assert_eq!(painter.commands()[0], DrawCommand::Rectangle {
+///     x: 10.0, y: 10.0, width: 100.0, height: 40.0, color: BLACK,
+/// });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RecordingPainter {
+    commands: Vec<DrawCommand>,
+}
+
+impl RecordingPainter {
+    /// Creates a new, empty [`RecordingPainter`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded commands in the order they were drawn.
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Takes the recorded commands, leaving the painter empty.
+    pub fn take_commands(&mut self) -> Vec<DrawCommand> {
+        std::mem::take(&mut self.commands)
+    }
+}
+
+impl TextMeasure for RecordingPainter {
+    fn measure_text(&self, text: &str, _font: Option<&Font>, font_size: u16) -> TextDimensions {
+        let size = font_size as f32;
+        TextDimensions {
+            width: text.chars().count() as f32 * size * 0.5,
+            height: size * 0.75,
+            offset_y: size * 0.75,
+        }
+    }
+}
+
+impl Painter for RecordingPainter {
+    fn draw_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        self.commands.push(DrawCommand::Rectangle { x, y, width, height, color });
+    }
+
+    fn draw_rectangle_lines(&mut self, x: f32, y: f32, width: f32, height: f32, thickness: f32, color: Color) {
+        self.commands.push(DrawCommand::RectangleLines { x, y, width, height, thickness, color });
+    }
+
+    fn draw_rounded_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, color: Color) {
+        self.commands.push(DrawCommand::RoundedRectangle { x, y, width, height, radius, color });
+    }
+
+    fn draw_rounded_rectangle_lines(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, thickness: f32, color: Color) {
+        self.commands.push(DrawCommand::RoundedRectangleLines { x, y, width, height, radius, thickness, color });
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        self.commands.push(DrawCommand::Line { x1, y1, x2, y2, thickness, color });
+    }
+
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, _font: Option<&Font>, font_size: u16, color: Color) {
+        self.commands.push(DrawCommand::Text { text: text.to_string(), x, y, font_size, color });
+    }
+
+    fn draw_texture(&mut self, texture: &Texture2D, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        self.commands.push(DrawCommand::Texture { texture: texture.clone(), x, y, width, height, color });
+    }
+
+    fn push_clip(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.commands.push(DrawCommand::PushClip { x, y, width, height });
+    }
+
+    fn pop_clip(&mut self) {
+        self.commands.push(DrawCommand::PopClip);
+    }
+}
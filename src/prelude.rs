@@ -1,7 +1,20 @@
+pub use crate::event::*;
+pub use crate::focus::*;
+pub use crate::input::*;
+pub use crate::layout::*;
+pub use crate::markup::*;
+pub use crate::painter::*;
+pub use crate::theme::*;
+#[cfg(feature = "serde")]
+pub use crate::themefile::*;
 pub use crate::widgets::widget::*;
 pub use crate::widgets::button::*;
 pub use crate::widgets::label::*;
 pub use crate::widgets::toggle::*;
 pub use crate::widgets::container::*;
+pub use crate::widgets::grid::*;
+pub use crate::widgets::stack::*;
+pub use crate::widgets::scrollview::*;
 pub use crate::widgets::image::*;
 pub use crate::widgets::textinput::*;
+pub use crate::widgets::textarea::*;
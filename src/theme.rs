@@ -0,0 +1,452 @@
+//! This module defines how widgets look.
+//!
+//! Every widget resolves its colors, font, border and padding from a [`Style`], which it takes
+//! from a [`Theme`]. Widgets made without explicit colors start with the global theme, and
+//! [`Widget::apply_theme`](crate::widgets::widget::Widget::apply_theme) restyles a whole tree,
+//! with every [`Container`](crate::widgets::container::Container) given its own theme passing it
+//! down to its subtree instead. Whatever a widget was given explicitly, through its constructor or
+//! a [`StyleOverride`], is kept when it's restyled.
+//!
+//! This module contains the following:
+//! 1. The [`Palette`] struct, which holds the base colors a [`Theme`] is made from.
+//! 2. The [`Style`] struct, which holds everything a single widget needs to draw itself.
+//! 3. The [`StyleOverride`] struct, which holds the parts of a [`Style`] a widget sets itself.
+//! 4. The [`Theme`] struct, which holds a [`Style`] for every kind of widget.
+//!
+//! ```
+//! use macroquad::prelude::*;
+//! use flowquad::prelude::*;
+//!
+//! let mut light = Theme::new(Palette::light());
+//! light.button.corner_radius = 6.0;
+//!
+//! let mut container = Container::themed(Direction::Vertical, Align::Start, 10.0);
+//! container.add_child(Box::new(Button::themed(100.0, 40.0, "Save".to_string())));
+//! container.add_child(Box::new(Button::themed(100.0, 40.0, "Delete".to_string())
+//!     .with_style(StyleOverride::new().bg(RED))));
+//!
+//! (&mut container as &mut dyn Widget).apply_theme(&light);
+//!
+//! let mut painter = RecordingPainter::new();
+//! container.render(0.0, 0.0, &mut painter);
+//! assert!(painter.commands().contains(&DrawCommand::RoundedRectangle {
+//!     x: 0.0, y: 0.0, width: 100.0, height: 40.0, radius: 6.0, color: light.button.bg,
+//! }));
+//! assert!(painter.commands().contains(&DrawCommand::RoundedRectangle {
+//!     x: 0.0, y: 50.0, width: 100.0, height: 40.0, radius: 6.0, color: RED,
+//! }));
+//! ```
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::painter::Painter;
+
+/// The space between a widget and the focus ring around it.
+const FOCUS_RING_GAP: f32 = 3.0;
+/// The width of the focus ring.
+const FOCUS_RING_WIDTH: f32 = 2.0;
+/// How far the foreground of a disabled widget is blended towards its background.
+pub(crate) const DISABLED_FADE: f32 = 0.5;
+
+/// Blends `from` towards `to` by `amount`, from 0 for `from` to 1 for `to`.
+pub(crate) fn mix(from: Color, to: Color, amount: f32) -> Color {
+    Color::new(
+        from.r + (to.r - from.r) * amount,
+        from.g + (to.g - from.g) * amount,
+        from.b + (to.b - from.b) * amount,
+        from.a + (to.a - from.a) * amount,
+    )
+}
+
+/// Parses a `"#rrggbb"` or `"#rrggbbaa"` color, the `#` is optional.
+pub(crate) fn parse_hex_color(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(digits, 16).ok()?;
+    match digits.len() {
+        6 => Some(Color::from_hex(value)),
+        8 => Some(Color::from_rgba((value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8)),
+        _ => None,
+    }
+}
+
+/// The [`Palette`] struct holds the base colors a [`Theme`] is made from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    /// The color behind everything.
+    pub background: Color,
+    /// The color of text and borders.
+    pub foreground: Color,
+    /// The color of things which stand out, like scrollbars and the focus ring.
+    pub accent: Color,
+    /// The color of things which are wrong, like the border of invalid text.
+    pub error: Color,
+}
+
+impl Palette {
+    /// Creates a new [`Palette`].
+    pub fn new(background: Color, foreground: Color, accent: Color, error: Color) -> Self {
+        Self {
+            background,
+            foreground,
+            accent,
+            error,
+        }
+    }
+
+    /// Returns the dark blue palette the examples use, which is the default.
+    pub fn dark() -> Self {
+        Self::new(
+            Color::new(0.05, 0.05, 0.1, 1.0),
+            Color::new(0.5, 0.5, 1.0, 1.0),
+            Color::new(0.7, 0.7, 1.0, 1.0),
+            RED,
+        )
+    }
+
+    /// Returns a palette with dark text on a light background.
+    pub fn light() -> Self {
+        Self::new(
+            Color::new(0.95, 0.95, 0.97, 1.0),
+            Color::new(0.15, 0.15, 0.3, 1.0),
+            Color::new(0.3, 0.3, 0.8, 1.0),
+            Color::new(0.8, 0.1, 0.1, 1.0),
+        )
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// The [`Style`] struct holds everything a single widget needs to draw itself. Interactive
+/// widgets swap `bg` and `fg` while they're hovered.
+///
+/// A `border_width` of zero draws no border, a `corner_radius` of zero draws square corners. The
+/// padding is left, right, top and bottom, like the padding of a
+/// [`Container`](crate::widgets::container::Container).
+#[derive(Clone)]
+pub struct Style {
+    pub bg: Color,
+    pub fg: Color,
+    pub font: Option<Font>,
+    pub font_size: f32,
+    pub border_width: f32,
+    pub corner_radius: f32,
+    pub padding: (f32, f32, f32, f32),
+}
+
+impl Style {
+    /// Creates a new [`Style`] with the default font, no border, square corners and no padding.
+    pub fn new(bg: Color, fg: Color, font_size: f32) -> Self {
+        Self {
+            bg,
+            fg,
+            font: None,
+            font_size,
+            border_width: 0.0,
+            corner_radius: 0.0,
+            padding: (0.0, 0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Sets the border width of the [`Style`].
+    pub fn with_border_width(mut self, border_width: f32) -> Self {
+        self.border_width = border_width;
+        self
+    }
+
+    /// Sets the corner radius of the [`Style`].
+    pub fn with_corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+
+    /// Sets the padding of the [`Style`].
+    pub fn with_padding(mut self, padding: (f32, f32, f32, f32)) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Fills the specified rectangle with `color`, rounding its corners by the corner radius.
+    pub(crate) fn draw_background(&self, painter: &mut dyn Painter, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        if self.corner_radius > 0.0 {
+            painter.draw_rounded_rectangle(x, y, width, height, self.corner_radius, color);
+        } else {
+            painter.draw_rectangle(x, y, width, height, color);
+        }
+    }
+
+    /// Draws the ring around the specified rectangle which shows a widget has the keyboard focus,
+    /// a little outside of it.
+    pub(crate) fn draw_focus_ring(&self, painter: &mut dyn Painter, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        let (x, y) = (x - FOCUS_RING_GAP, y - FOCUS_RING_GAP);
+        let (width, height) = (width + FOCUS_RING_GAP * 2.0, height + FOCUS_RING_GAP * 2.0);
+        if self.corner_radius > 0.0 {
+            painter.draw_rounded_rectangle_lines(x, y, width, height, self.corner_radius + FOCUS_RING_GAP, FOCUS_RING_WIDTH, color);
+        } else {
+            painter.draw_rectangle_lines(x, y, width, height, FOCUS_RING_WIDTH, color);
+        }
+    }
+
+    /// Draws the border around the specified rectangle in `color`, if the style has one.
+    pub(crate) fn draw_border(&self, painter: &mut dyn Painter, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        if self.border_width <= 0.0 {
+            return;
+        }
+        if self.corner_radius > 0.0 {
+            painter.draw_rounded_rectangle_lines(x, y, width, height, self.corner_radius, self.border_width, color);
+        } else {
+            painter.draw_rectangle_lines(x, y, width, height, self.border_width, color);
+        }
+    }
+}
+
+/// The [`StyleOverride`] struct holds the parts of a [`Style`] a widget sets itself. They win
+/// over the [`Theme`] whenever the widget is restyled.
+///
+/// It can also name one of the [`Theme::named`] styles, which is applied over the theme's style
+/// for the kind of widget before the rest of the overrides.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let theme = Theme::default();
+/// let style = StyleOverride::new().fg(YELLOW).border_width(0.0).apply(&theme.button);
+/// assert_eq!(style.fg, YELLOW);
+/// assert_eq!(style.border_width, 0.0);
+/// assert_eq!(style.bg, theme.button.bg);
+/// ```
+#[derive(Clone, Default)]
+pub struct StyleOverride {
+    pub bg: Option<Color>,
+    pub fg: Option<Color>,
+    pub font: Option<Font>,
+    pub font_size: Option<f32>,
+    pub border_width: Option<f32>,
+    pub corner_radius: Option<f32>,
+    pub padding: Option<(f32, f32, f32, f32)>,
+    pub name: Option<String>,
+}
+
+impl StyleOverride {
+    /// Creates a new [`StyleOverride`] which doesn't override anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`StyleOverride`] which uses the [`Theme::named`] style called `name`.
+    pub fn named(name: String) -> Self {
+        Self {
+            name: Some(name),
+            ..Self::default()
+        }
+    }
+
+    /// Overrides the background color.
+    pub fn bg(mut self, bg: Color) -> Self {
+        self.bg = Some(bg);
+        self
+    }
+
+    /// Overrides the foreground color.
+    pub fn fg(mut self, fg: Color) -> Self {
+        self.fg = Some(fg);
+        self
+    }
+
+    /// Overrides the font.
+    pub fn font(mut self, font: Font) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Overrides the font size.
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    /// Overrides the border width.
+    pub fn border_width(mut self, border_width: f32) -> Self {
+        self.border_width = Some(border_width);
+        self
+    }
+
+    /// Overrides the corner radius.
+    pub fn corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = Some(corner_radius);
+        self
+    }
+
+    /// Overrides the padding.
+    pub fn padding(mut self, padding: (f32, f32, f32, f32)) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Returns `style` with the named style from `theme` applied, if there is one, and then
+    /// everything this overrides replaced.
+    pub fn resolve(&self, theme: &Theme, style: &Style) -> Style {
+        match self.name.as_ref().and_then(|name| theme.named.get(name)) {
+            Some(named) => self.apply(&named.apply(style)),
+            None => self.apply(style),
+        }
+    }
+
+    /// Returns `style` with everything this overrides replaced, ignoring the name.
+    pub fn apply(&self, style: &Style) -> Style {
+        Style {
+            bg: self.bg.unwrap_or(style.bg),
+            fg: self.fg.unwrap_or(style.fg),
+            font: self.font.clone().or_else(|| style.font.clone()),
+            font_size: self.font_size.unwrap_or(style.font_size),
+            border_width: self.border_width.unwrap_or(style.border_width),
+            corner_radius: self.corner_radius.unwrap_or(style.corner_radius),
+            padding: self.padding.unwrap_or(style.padding),
+        }
+    }
+
+    /// Returns these overrides with everything `other` overrides replaced.
+    pub fn merge(&self, other: &StyleOverride) -> Self {
+        Self {
+            bg: other.bg.or(self.bg),
+            fg: other.fg.or(self.fg),
+            font: other.font.clone().or_else(|| self.font.clone()),
+            font_size: other.font_size.or(self.font_size),
+            border_width: other.border_width.or(self.border_width),
+            corner_radius: other.corner_radius.or(self.corner_radius),
+            padding: other.padding.or(self.padding),
+            name: other.name.clone().or_else(|| self.name.clone()),
+        }
+    }
+}
+
+/// The [`Theme`] struct holds a [`Style`] for every kind of widget, made from a [`Palette`]. The
+/// styles are public so single kinds of widgets can be tweaked after the theme is made.
+///
+/// A theme also holds named styles, which widgets pick with [`StyleOverride::named`], for the
+/// widgets which look different from the rest of their kind.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let theme = Theme::default().with_named_style("danger".to_string(), StyleOverride::new().fg(RED));
+/// let mut button = Button::themed(100.0, 40.0, "Delete".to_string())
+///     .with_style(StyleOverride::named("danger".to_string()));
+/// button.apply_theme(&theme);
+///
+/// let mut painter = RecordingPainter::new();
+/// button.render(0.0, 0.0, &mut painter);
+/// assert!(painter.commands().iter().any(|c| matches!(c, DrawCommand::Text { color, .. } if *color == RED)));
+/// ```
+#[derive(Clone)]
+pub struct Theme {
+    pub palette: Palette,
+    pub button: Style,
+    pub toggle: Style,
+    pub label: Style,
+    pub text_input: Style,
+    pub text_area: Style,
+    pub container: Style,
+    pub grid: Style,
+    pub stack: Style,
+    pub scroll_view: Style,
+    pub named: HashMap<String, StyleOverride>,
+}
+
+thread_local! {
+    /// The theme widgets made without explicit colors start with.
+    static GLOBAL_THEME: RefCell<Theme> = RefCell::new(Theme::default());
+}
+
+impl Theme {
+    /// Creates a new [`Theme`] from the specified [`Palette`], with the default font.
+    pub fn new(palette: Palette) -> Self {
+        let Palette { background, foreground, accent, .. } = palette;
+        Self {
+            palette,
+            button: Style::new(background, foreground, 20.0).with_border_width(4.0),
+            toggle: Style::new(background, foreground, 20.0).with_border_width(4.0),
+            label: Style::new(BLANK, foreground, 32.0),
+            text_input: Style::new(background, foreground, 20.0)
+                .with_border_width(4.0)
+                .with_padding((4.0, 4.0, 8.0, 8.0)),
+            text_area: Style::new(background, foreground, 20.0)
+                .with_border_width(4.0)
+                .with_padding((4.0, 4.0, 4.0, 4.0)),
+            container: Style::new(background, foreground, 20.0),
+            grid: Style::new(BLANK, foreground, 20.0),
+            stack: Style::new(BLANK, foreground, 20.0),
+            scroll_view: Style::new(background, accent, 20.0),
+            named: HashMap::new(),
+        }
+    }
+
+    /// Sets the font of every [`Style`] in the [`Theme`].
+    pub fn with_font(mut self, font: Font) -> Self {
+        for style in self.styles_mut() {
+            style.font = Some(font.clone());
+        }
+        self
+    }
+
+    /// Adds a named style to the [`Theme`], replacing any with the same name.
+    pub fn with_named_style(mut self, name: String, style: StyleOverride) -> Self {
+        self.named.insert(name, style);
+        self
+    }
+
+    /// Multiplies the font size of every [`Style`] in the [`Theme`] by `scale`.
+    pub fn with_font_scale(mut self, scale: f32) -> Self {
+        for style in self.styles_mut() {
+            style.font_size *= scale;
+        }
+        self
+    }
+
+    /// Returns a copy of the global [`Theme`], which widgets made without explicit colors start
+    /// with.
+    pub fn global() -> Theme {
+        GLOBAL_THEME.with_borrow(Theme::clone)
+    }
+
+    /// Calls `f` with the global [`Theme`], without copying it.
+    pub(crate) fn with_global<R>(f: impl FnOnce(&Theme) -> R) -> R {
+        GLOBAL_THEME.with_borrow(f)
+    }
+
+    /// Replaces the global [`Theme`]. Widgets which already exist keep their style until they're
+    /// restyled with [`Widget::apply_theme`](crate::widgets::widget::Widget::apply_theme).
+    pub fn set_global(theme: Theme) {
+        GLOBAL_THEME.set(theme);
+    }
+
+    fn styles_mut(&mut self) -> [&mut Style; 9] {
+        [
+            &mut self.button,
+            &mut self.toggle,
+            &mut self.label,
+            &mut self.text_input,
+            &mut self.text_area,
+            &mut self.container,
+            &mut self.grid,
+            &mut self.stack,
+            &mut self.scroll_view,
+        ]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(Palette::default())
+    }
+}
@@ -0,0 +1,352 @@
+//! This module defines how [`Theme`]s are loaded from TOML files, it's only available with the
+//! `serde` feature.
+//!
+//! A theme file has an optional `[palette]`, which the styles of every kind of widget are made
+//! from, then optional tables for the kinds of widgets which override parts of their style, and
+//! named styles under `[named.<name>]`. Anything left out keeps its value from the default dark
+//! [`Theme`]. Colors are either `"#rrggbb"` or `"#rrggbbaa"` strings, or lists of three or four
+//! numbers from 0 to 1. Paddings are either a single number or a list of left, right, top and
+//! bottom. Fonts are paths to TTF files, relative to the working directory.
+//!
+//! ```toml
+//! font = "examples/poppins.ttf"
+//!
+//! [palette]
+//! background = "#0d0d1a"
+//! foreground = [0.5, 0.5, 1.0]
+//!
+//! [button]
+//! corner_radius = 6
+//! padding = [8, 8, 4, 4]
+//!
+//! [named.danger]
+//! fg = "#ff4040"
+//! ```
+//!
+//! This module contains the following:
+//! 1. The [`ThemeError`] enum, which defines what can go wrong while loading a theme.
+//! 2. The [`ThemeWatcher`] struct, which reloads a theme file whenever it changes and restyles a
+//!    live widget tree with it.
+//! 3. The [`Theme::from_toml`] and [`Theme::load`] functions, which load a theme once.
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+use crate::theme::{parse_hex_color, Palette, Style, StyleOverride, Theme};
+use crate::widgets::widget::Widget;
+
+/// The [`ThemeError`] enum defines what can go wrong while loading a [`Theme`].
+#[derive(Debug)]
+pub enum ThemeError {
+    /// The theme file couldn't be read.
+    Io(io::Error),
+    /// The theme file isn't valid TOML, or doesn't describe a theme.
+    Parse(toml::de::Error),
+    /// A color isn't a valid hex string.
+    Color(String),
+    /// A font file couldn't be read or loaded.
+    Font(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(error) => write!(f, "couldn't read the theme: {}", error),
+            ThemeError::Parse(error) => write!(f, "invalid theme: {}", error),
+            ThemeError::Color(color) => write!(f, "invalid color \"{}\", expected \"#rrggbb\" or \"#rrggbbaa\"", color),
+            ThemeError::Font(error) => write!(f, "couldn't load font: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Io(error) => Some(error),
+            ThemeError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorDef {
+    Hex(String),
+    Rgb([f32; 3]),
+    Rgba([f32; 4]),
+}
+
+impl ColorDef {
+    fn to_color(&self) -> Result<Color, ThemeError> {
+        match self {
+            ColorDef::Hex(hex) => parse_hex_color(hex).ok_or_else(|| ThemeError::Color(hex.clone())),
+            ColorDef::Rgb([r, g, b]) => Ok(Color::new(*r, *g, *b, 1.0)),
+            ColorDef::Rgba([r, g, b, a]) => Ok(Color::new(*r, *g, *b, *a)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PaddingDef {
+    Even(f32),
+    Sides([f32; 4]),
+}
+
+impl PaddingDef {
+    fn to_padding(&self) -> (f32, f32, f32, f32) {
+        match *self {
+            PaddingDef::Even(padding) => (padding, padding, padding, padding),
+            PaddingDef::Sides([left, right, top, bottom]) => (left, right, top, bottom),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PaletteDef {
+    background: Option<ColorDef>,
+    foreground: Option<ColorDef>,
+    accent: Option<ColorDef>,
+    error: Option<ColorDef>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StyleDef {
+    bg: Option<ColorDef>,
+    fg: Option<ColorDef>,
+    font: Option<String>,
+    font_size: Option<f32>,
+    border_width: Option<f32>,
+    corner_radius: Option<f32>,
+    padding: Option<PaddingDef>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeDef {
+    font: Option<String>,
+    font_scale: Option<f32>,
+    palette: PaletteDef,
+    button: StyleDef,
+    toggle: StyleDef,
+    label: StyleDef,
+    text_input: StyleDef,
+    text_area: StyleDef,
+    container: StyleDef,
+    grid: StyleDef,
+    stack: StyleDef,
+    scroll_view: StyleDef,
+    named: HashMap<String, StyleDef>,
+}
+
+/// Loads the fonts of a theme, so every path is only read once.
+#[derive(Default)]
+struct FontCache {
+    fonts: HashMap<String, Font>,
+}
+
+impl FontCache {
+    fn load(&mut self, path: &str) -> Result<Font, ThemeError> {
+        if let Some(font) = self.fonts.get(path) {
+            return Ok(font.clone());
+        }
+        let bytes = fs::read(path).map_err(|error| ThemeError::Font(format!("{}: {}", path, error)))?;
+        let font = load_ttf_font_from_bytes(&bytes).map_err(|error| ThemeError::Font(format!("{}: {}", path, error)))?;
+        self.fonts.insert(path.to_string(), font.clone());
+        Ok(font)
+    }
+}
+
+fn color(def: &Option<ColorDef>) -> Result<Option<Color>, ThemeError> {
+    def.as_ref().map(ColorDef::to_color).transpose()
+}
+
+impl StyleDef {
+    fn to_override(&self, fonts: &mut FontCache) -> Result<StyleOverride, ThemeError> {
+        Ok(StyleOverride {
+            bg: color(&self.bg)?,
+            fg: color(&self.fg)?,
+            font: self.font.as_deref().map(|path| fonts.load(path)).transpose()?,
+            font_size: self.font_size,
+            border_width: self.border_width,
+            corner_radius: self.corner_radius,
+            padding: self.padding.as_ref().map(PaddingDef::to_padding),
+            name: None,
+        })
+    }
+}
+
+impl ThemeDef {
+    fn to_theme(&self) -> Result<Theme, ThemeError> {
+        let default = Palette::default();
+        let palette = Palette::new(
+            color(&self.palette.background)?.unwrap_or(default.background),
+            color(&self.palette.foreground)?.unwrap_or(default.foreground),
+            color(&self.palette.accent)?.unwrap_or(default.accent),
+            color(&self.palette.error)?.unwrap_or(default.error),
+        );
+
+        let mut fonts = FontCache::default();
+        let mut theme = Theme::new(palette);
+        if let Some(path) = &self.font {
+            theme = theme.with_font(fonts.load(path)?);
+        }
+        if let Some(scale) = self.font_scale {
+            theme = theme.with_font_scale(scale);
+        }
+
+        let styles: [(&mut Style, &StyleDef); 9] = [
+            (&mut theme.button, &self.button),
+            (&mut theme.toggle, &self.toggle),
+            (&mut theme.label, &self.label),
+            (&mut theme.text_input, &self.text_input),
+            (&mut theme.text_area, &self.text_area),
+            (&mut theme.container, &self.container),
+            (&mut theme.grid, &self.grid),
+            (&mut theme.stack, &self.stack),
+            (&mut theme.scroll_view, &self.scroll_view),
+        ];
+        for (style, def) in styles {
+            *style = def.to_override(&mut fonts)?.apply(style);
+        }
+        for (name, def) in &self.named {
+            theme.named.insert(name.clone(), def.to_override(&mut fonts)?);
+        }
+        Ok(theme)
+    }
+}
+
+impl Theme {
+    /// Loads a [`Theme`] from the TOML in `source`. See the [module](crate::themefile) for the
+    /// format.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let theme = Theme::from_toml(r##"
+    ///     [palette]
+    ///     foreground = "#ffffff"
+    ///
+    ///     [button]
+    ///     corner_radius = 6
+    ///     padding = 4
+    ///
+    ///     [named.danger]
+    ///     fg = [1.0, 0.0, 0.0]
+    /// "##).unwrap();
+    ///
+    /// assert_eq!(theme.label.fg, WHITE);
+    /// assert_eq!(theme.button.corner_radius, 6.0);
+    /// assert_eq!(theme.button.padding, (4.0, 4.0, 4.0, 4.0));
+    /// assert_eq!(theme.named["danger"].fg, Some(Color::new(1.0, 0.0, 0.0, 1.0)));
+    ///
+    /// assert!(Theme::from_toml("[button]\nbg = \"#12345\"").is_err());
+    /// assert!(Theme::from_toml("[buton]").is_err());
+    /// ```
+    pub fn from_toml(source: &str) -> Result<Theme, ThemeError> {
+        toml::from_str::<ThemeDef>(source)
+            .map_err(ThemeError::Parse)?
+            .to_theme()
+    }
+
+    /// Loads a [`Theme`] from the TOML file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Theme, ThemeError> {
+        let source = fs::read_to_string(path).map_err(ThemeError::Io)?;
+        Theme::from_toml(&source)
+    }
+}
+
+/// The [`ThemeWatcher`] struct reloads a theme file whenever it's saved, so a [`Theme`] can be
+/// tweaked while the app runs.
+///
+/// The file is checked every time [`ThemeWatcher::poll`] is called, which is meant to be once per
+/// frame. A file which fails to load is reported once, and the last good theme is kept until it's
+/// fixed.
+///
+/// ```no_run
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// #[macroquad::main("Hot Reload")]
+/// async fn main() {
+///     let mut watcher = ThemeWatcher::new("theme.toml").unwrap();
+///     let mut container = Container::themed(Direction::Vertical, Align::Center, 10.0);
+///     container.add_child(Box::new(Button::themed(200.0, 50.0, "Play".to_string())));
+///
+///     loop {
+///         if let Err(error) = watcher.apply_changes(&mut container) {
+///             eprintln!("{}", error);
+///         }
+///
+///         let mut input = InputState::capture();
+///         container.update(0.0, 0.0, &mut input);
+///         container.render(0.0, 0.0, &mut MacroquadPainter);
+///         next_frame().await;
+///     }
+/// }
+/// ```
+pub struct ThemeWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    theme: Theme,
+}
+
+impl ThemeWatcher {
+    /// Creates a new [`ThemeWatcher`] for the file at `path`, loads the theme and makes it the
+    /// global [`Theme`], so widgets made from now on use it.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, ThemeError> {
+        let path = path.into();
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        let theme = Theme::load(&path)?;
+        Theme::set_global(theme.clone());
+        Ok(Self {
+            path,
+            modified,
+            theme,
+        })
+    }
+
+    /// Returns the last theme which loaded.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Reloads the theme if the file changed since it was last loaded, returns if it did. The new
+    /// theme is made the global [`Theme`]. A missing file counts as unchanged, since editors often
+    /// replace the file while saving, so the last theme which loaded is kept.
+    pub fn poll(&mut self) -> Result<bool, ThemeError> {
+        let modified = match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(error) => return Err(ThemeError::Io(error)),
+        };
+        if self.modified == Some(modified) {
+            return Ok(false);
+        }
+        self.modified = Some(modified);
+
+        self.theme = Theme::load(&self.path)?;
+        Theme::set_global(self.theme.clone());
+        Ok(true)
+    }
+
+    /// Reloads the theme if the file changed, and restyles `root` and its descendants with it.
+    /// Returns if the theme changed.
+    pub fn apply_changes(&mut self, root: &mut dyn Widget) -> Result<bool, ThemeError> {
+        let changed = self.poll()?;
+        if changed {
+            root.apply_theme(&self.theme);
+        }
+        Ok(changed)
+    }
+}
@@ -1,36 +1,249 @@
-//! This module defines the [`Button`] widget that can be clicked to perform an action.
+//! This module defines the [`Button`] widget that can be clicked to perform an action, and the
+//! [`ButtonBuilder`] which makes one.
 use std::any::Any;
+use std::collections::HashSet;
 
 use macroquad::prelude::*;
 
+use crate::event::Event;
+use crate::input::{InputState, DOUBLE_CLICK_TIME};
+use crate::layout::{Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::{mix, Style, StyleOverride, Theme, DISABLED_FADE};
+
 use super::widget::{Widget, Action};
 
-/// A [`Button`] widget that can be clicked to perform an action.
+/// The mouse buttons a [`Button`] can be clicked with.
+const MOUSE_BUTTONS: [MouseButton; 3] = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// How far the background of a pressed [`Button`] is blended from its foreground color towards
+/// its background color.
+const PRESSED_SHADE: f32 = 0.3;
+
+/// A [`Button`] widget that can be clicked to perform an action. While it has the keyboard focus,
+/// the confirm action of the [`FocusManager`](crate::focus::FocusManager) clicks it too.
+///
+/// The button is clicked when a mouse button is released over it, after being pressed on it, so a
+/// click can be cancelled by dragging off it before letting go. Right, middle and double clicks
+/// are reported through [`Action`], while only left clicks emit an [`Event`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut button = Button::builder().size(100.0, 40.0).build();
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(50.0, 20.0).press_mouse(MouseButton::Left).move_mouse(150.0, 20.0).release_mouse(MouseButton::Left)
+///     .move_mouse(50.0, 20.0).click(MouseButton::Right).click(MouseButton::Left).click(MouseButton::Left);
+///
+/// let mut clicks = Vec::new();
+/// while !input.is_finished() {
+///     button.update(0.0, 0.0, &mut input.poll());
+///     if button.is_pressed() {
+///         clicks.push("pressed");
+///     }
+///     if button.is_right_clicked() {
+///         clicks.push("right");
+///     }
+///     if button.is_double_clicked() {
+///         clicks.push("double");
+///     } else if button.is_clicked() {
+///         clicks.push("left");
+///     }
+/// }
+/// assert_eq!(clicks, vec!["pressed", "pressed", "right", "pressed", "left", "pressed", "double"]);
+/// ```
 pub struct Button {
     width: f32,
     height: f32,
     text: String,
-    bg: Color,
-    fg: Color,
+    style: Style,
+    overrides: StyleOverride,
+    /// The color of the focus ring.
+    ring: Color,
     hover: bool,
-    click: bool,
-    font: Option<Font>,
+    /// The mouse buttons which were pressed on the button and are still held down.
+    held: HashSet<MouseButton>,
+    /// The mouse buttons the button was clicked with this frame.
+    clicked: HashSet<MouseButton>,
+    double_clicked: bool,
+    last_click: f64,
+    focused: bool,
+    activated: bool,
+    position: (f32, f32),
+    enabled: bool,
+    /// If the containers around the widget are enabled.
+    inherited_enabled: bool,
+    preferred: Size,
+    id: Option<String>,
+    events: Vec<Event>,
+    on_click: Option<Box<dyn FnMut()>>,
 }
 
 impl Button {
-    /// Creates a new [`Button`] widget.
+    /// Creates a new [`Button`] widget with the specified colors and font, which are kept when it's
+    /// restyled. The text is sized from the height.
     pub fn new(width: f32, height: f32, text: String, bg: Color, fg: Color, font: Option<Font>) -> Self {
+        Self::themed(width, height, text).with_style(StyleOverride {
+            bg: Some(bg),
+            fg: Some(fg),
+            font,
+            font_size: Some(height * 0.4),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new [`Button`] widget styled by the global [`Theme`].
+    pub fn themed(width: f32, height: f32, text: String) -> Self {
+        let theme = Theme::global();
         Self {
             width,
             height,
             text,
-            bg,
-            fg,
+            style: theme.button,
+            overrides: StyleOverride::new(),
+            ring: theme.palette.accent,
             hover: false,
-            click: false,
-            font,
+            held: HashSet::new(),
+            clicked: HashSet::new(),
+            double_clicked: false,
+            last_click: f64::NEG_INFINITY,
+            focused: false,
+            activated: false,
+            position: (0.0, 0.0),
+            enabled: true,
+            inherited_enabled: true,
+            preferred: Size::new(width, height),
+            id: None,
+            events: Vec::new(),
+            on_click: None,
         }
     }
+
+    /// Returns a [`ButtonBuilder`] for a 200 by 50 button without text, styled by the global
+    /// [`Theme`].
+    pub fn builder() -> ButtonBuilder {
+        ButtonBuilder {
+            button: Button::themed(200.0, 50.0, String::new()),
+        }
+    }
+
+    /// Overrides part of the style of the [`Button`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self
+    }
+
+    /// Enables or disables the [`Button`] widget. A disabled button is dimmed, can't take the focus
+    /// and ignores the mouse and the keyboard, so it emits no events.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Enables or disables the [`Button`] widget, see [`Button::with_enabled`].
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Sets the ID of the [`Button`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets a callback which is called every time the [`Button`] is clicked.
+    pub fn on_click(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.on_click = Some(Box::new(callback));
+        self
+    }
+}
+
+/// The [`ButtonBuilder`] struct makes a [`Button`] one setting at a time, made with
+/// [`Button::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let button = Button::builder()
+///     .size(120.0, 40.0)
+///     .text("Play".to_string())
+///     .fg(YELLOW)
+///     .on_click(|| println!("Playing!"))
+///     .build();
+/// assert_eq!((button.width(), button.height()), (120.0, 40.0));
+/// ```
+pub struct ButtonBuilder {
+    button: Button,
+}
+
+impl ButtonBuilder {
+    /// Sets the size of the button.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.button.width = width;
+        self.button.height = height;
+        self.button.preferred = Size::new(width, height);
+        self
+    }
+
+    /// Sets the text of the button.
+    pub fn text(mut self, text: String) -> Self {
+        self.button.text = text;
+        self
+    }
+
+    /// Enables or disables the button, see [`Button::with_enabled`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.button = self.button.with_enabled(enabled);
+        self
+    }
+
+    /// Sets the ID of the button, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.button = self.button.with_id(id);
+        self
+    }
+
+    /// Overrides part of the style of the button, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.button = self.button.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the button.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Sets the foreground color of the button.
+    pub fn fg(self, fg: Color) -> Self {
+        self.style(StyleOverride::new().fg(fg))
+    }
+
+    /// Sets the font of the button.
+    pub fn font(self, font: Font) -> Self {
+        self.style(StyleOverride::new().font(font))
+    }
+
+    /// Sets the font size of the button.
+    pub fn font_size(self, font_size: f32) -> Self {
+        self.style(StyleOverride::new().font_size(font_size))
+    }
+
+    /// Sets a callback which is called every time the button is clicked.
+    pub fn on_click(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.button = self.button.on_click(callback);
+        self
+    }
+
+    /// Makes the [`Button`].
+    pub fn build(self) -> Button {
+        self.button
+    }
 }
 
 impl Widget for Button {
@@ -38,6 +251,42 @@ impl Widget for Button {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.is_enabled()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled && self.inherited_enabled
+    }
+
+    fn set_inherited_enabled(&mut self, enabled: bool) {
+        self.inherited_enabled = enabled;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn activate(&mut self) {
+        self.activated = true;
+    }
+
+    fn screen_rect(&self) -> Option<Rect> {
+        Some(Rect::new(self.position.0, self.position.1, self.width, self.height))
+    }
+
     fn width(&self) -> f32 {
         self.width
     }
@@ -47,49 +296,134 @@ impl Widget for Button {
     }
 
     fn bg(&self) -> Color {
-        self.bg
+        self.style.bg
     }
 
-    fn update(&mut self, x: f32, y: f32) {
-        let mouse_pos = mouse_position();
-        let mx = mouse_pos.0;
-        let my = mouse_pos.1;
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.button);
+        self.ring = theme.palette.accent;
+    }
 
-        self.hover = mx >= x && mx <= x + self.width && my >= y && my <= y + self.height;
-        self.click = self.hover && is_mouse_button_pressed(MouseButton::Left);
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let size = constraints.constrain(self.preferred);
+        self.width = size.width;
+        self.height = size.height;
+        size
     }
 
-    fn render(&self, x: f32, y: f32) {
-        let bg = if self.hover { self.fg } else { self.bg };
-        let fg = if self.hover { self.bg } else { self.fg };
+    fn drain_events(&mut self, events: &mut Vec<Event>) {
+        events.append(&mut self.events);
+    }
 
-        draw_rectangle(x, y, self.width, self.height, bg);
-        
-        let size = (self.height * 0.4) as u16;
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        self.position = (x, y);
+        if !self.is_enabled() {
+            self.hover = false;
+            self.held.clear();
+            self.clicked.clear();
+            self.double_clicked = false;
+            self.activated = false;
+            self.focused = false;
+            self.events.clear();
+            // A disabled button still covers whatever is under it
+            if input.hit_test(x, y, self.width, self.height) {
+                input.consume_mouse();
+            }
+            return;
+        }
+        self.hover = input.hit_test(x, y, self.width, self.height);
+        if self.hover || !self.held.is_empty() {
+            input.consume_mouse();
+        }
+        if input.is_mouse_button_pressed(MouseButton::Left) && !self.hover {
+            self.focused = false;
+        }
+
+        // A click needs the mouse button to be pressed and released over the button
+        self.clicked.clear();
+        for button in MOUSE_BUTTONS {
+            if self.hover && input.is_mouse_button_pressed(button) {
+                self.held.insert(button);
+            }
+            if !input.is_mouse_button_down(button) && self.held.remove(&button) && self.hover {
+                self.clicked.insert(button);
+            }
+        }
+
+        self.double_clicked = false;
+        if self.clicked.contains(&MouseButton::Left) {
+            if input.time - self.last_click <= DOUBLE_CLICK_TIME {
+                self.double_clicked = true;
+                self.last_click = f64::NEG_INFINITY;
+            } else {
+                self.last_click = input.time;
+            }
+        }
+        if std::mem::take(&mut self.activated) {
+            self.clicked.insert(MouseButton::Left);
+        }
+
+        self.events.clear();
+        if self.is_clicked() {
+            self.events.push(Event::Clicked { id: self.id.clone() });
+            if let Some(on_click) = &mut self.on_click {
+                on_click();
+            }
+        }
+    }
+
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let (bg, fg) = if !self.is_enabled() {
+            (self.style.bg, mix(self.style.fg, self.style.bg, DISABLED_FADE))
+        } else if self.hover && self.is_pressed() {
+            (mix(self.style.fg, self.style.bg, PRESSED_SHADE), self.style.bg)
+        } else if self.hover {
+            (self.style.fg, self.style.bg)
+        } else {
+            (self.style.bg, self.style.fg)
+        };
+
+        self.style.draw_background(painter, x, y, self.width, self.height, bg);
+
+        let size = self.style.font_size as u16;
         
-        let text_size = measure_text(&self.text, self.font.as_ref(), size, 1.0);
-        draw_text_ex(&self.text,
+        let text_size = painter.measure_text(&self.text, self.style.font.as_ref(), size);
+        painter.draw_text(&self.text,
             x + self.width / 2.0 - text_size.width / 2.0,
             y + self.height / 2.0 + text_size.height / 4.0,
-            TextParams {
-                font: self.font.as_ref(),
-                font_size: size,
-                font_scale: 1.0,
-                color: fg,
-                ..Default::default()
-            }
+            self.style.font.as_ref(), size, fg
         );
 
-        draw_rectangle_lines(x, y, self.width, self.height, 4.0, fg);
+        self.style.draw_border(painter, x, y, self.width, self.height, fg);
+
+        if self.focused {
+            self.style.draw_focus_ring(painter, x, y, self.width, self.height, self.ring);
+        }
     }
 }
 
 impl Action for Button {
     fn is_clicked(&self) -> bool {
-        self.click
+        self.clicked.contains(&MouseButton::Left)
     }
 
     fn is_hovered(&self) -> bool {
         self.hover
     }
+
+    fn is_pressed(&self) -> bool {
+        self.held.contains(&MouseButton::Left)
+    }
+
+    fn is_right_clicked(&self) -> bool {
+        self.clicked.contains(&MouseButton::Right)
+    }
+
+    fn is_middle_clicked(&self) -> bool {
+        self.clicked.contains(&MouseButton::Middle)
+    }
+
+    fn is_double_clicked(&self) -> bool {
+        self.double_clicked
+    }
 }
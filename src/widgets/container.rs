@@ -2,28 +2,99 @@
 //!
 //! This module contains the following:
 //! 1. The [`Container`] struct, which is a container for other widgets, it itself implements the
-//!     [`Widget`] trait.
+//!    [`Widget`] trait.
 //! 2. The [`Direction`] enum, which defines the direction of the container, either horizontal or
-//!     vertical.
-//! 3. The [`Align`] enum, which defines the alignment of the container, either start, center or
-//!     end.
+//!    vertical, and if the children wrap onto new lines.
+//! 3. The [`Align`] enum, which defines the alignment of the children on the cross axis, either
+//!    start, center, end or stretch.
+//! 4. The [`Justify`] enum, which defines how the children are spread along the main axis.
+//! 5. The [`Flex`] struct, which defines how much a child grows or shrinks to fill the container.
+//! 6. The [`ContainerBuilder`] struct, which makes a [`Container`] one setting at a time.
 use std::any::Any;
+use std::cell::Cell;
+use std::ops::Range;
 
 use macroquad::prelude::*;
 
+use crate::event::Event;
+use crate::input::InputState;
+use crate::layout::{intrinsic_sizes, layout_unless_laid_out, Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::{Style, StyleOverride, Theme};
+
 use super::widget::Widget;
 
-/// The [`Direction`] enum defines the direction of the container, either horizontal or vertical.
+/// The [`Direction`] enum defines the direction of the container, either horizontal or vertical,
+/// and if the children wrap onto new lines when they don't fit.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Direction {
     Horizontal,
     Vertical,
+    /// The children are placed left to right, and wrap onto a new row below when the next one
+    /// doesn't fit in the max width of the container.
+    HorizontalWrap,
+    /// The children are placed top to bottom, and wrap onto a new column to the right when the
+    /// next one doesn't fit in the max height of the container.
+    VerticalWrap,
 }
 
-/// The [`Align`] enum defines the alignment of the container, either start, center or end.
+/// The [`Align`] enum defines the alignment of the children on the cross axis, either start,
+/// center, end or stretch, which makes every child as wide or tall as the container.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Align {
     Start,
     Center,
     End,
+    Stretch,
+}
+
+/// The [`Justify`] enum defines how the children are spread along the main axis when the
+/// container is bigger than they are.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Justify {
+    /// The children are packed at the start.
+    #[default]
+    Start,
+    /// The children are packed in the center.
+    Center,
+    /// The children are packed at the end.
+    End,
+    /// The first and last children touch the edges and the space is shared between the others.
+    SpaceBetween,
+    /// Every child gets the same space on both sides, so the space between children is twice the
+    /// space at the edges.
+    SpaceAround,
+    /// The space between children and at the edges is the same.
+    SpaceEvenly,
+}
+
+/// The [`Flex`] struct defines how a child of a [`Container`] takes up the space along the main
+/// axis. When there's space left, it's shared between the children in proportion to their `grow`
+/// factors. When the children don't fit, they're shrunk in proportion to their `shrink` factors
+/// times their size.
+///
+/// Children don't grow or shrink by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Flex {
+    pub grow: f32,
+    pub shrink: f32,
+}
+
+impl Flex {
+    /// Creates a new [`Flex`].
+    pub fn new(grow: f32, shrink: f32) -> Self {
+        Self { grow, shrink }
+    }
+
+    /// Creates a [`Flex`] which grows by the specified factor and doesn't shrink.
+    pub fn grow(grow: f32) -> Self {
+        Self::new(grow, 0.0)
+    }
+
+    /// Creates a [`Flex`] which shrinks by the specified factor and doesn't grow.
+    pub fn shrink(shrink: f32) -> Self {
+        Self::new(0.0, shrink)
+    }
 }
 
 /// The [`Container`] struct is a container for other widgets, it itself implements the [`Widget`],
@@ -33,34 +104,264 @@ pub struct Container {
     direction: Direction,
     align: Align,
     gap: f32,
+    line_gap: f32,
     children: Vec<Box<dyn Widget>>,
-    bg: Color,
-    padding: Option<(f32, f32, f32, f32)>,
-    border: Option<(f32, Color)>,
+    flex: Vec<Flex>,
+    justify: Justify,
+    constraints: Constraints,
+    style: Style,
+    overrides: StyleOverride,
+    /// The theme of the container's subtree, instead of the one it's restyled with.
+    theme: Option<Theme>,
+    enabled: bool,
+    /// If the containers around the container are enabled.
+    inherited_enabled: bool,
+    id: Option<String>,
+    /// The size from the last layout, if the container was laid out since its children changed.
+    size: Option<Size>,
+    /// The size the container takes up before it's laid out, measured once since its children
+    /// changed, as it's measured from the whole subtree.
+    intrinsic: Cell<Option<Size>>,
+    /// Where every child goes, relative to the container, from the last layout.
+    rects: Vec<Rect>,
+    /// Whether the container was laid out since the last update, and hasn't changed since.
+    laid_out: bool,
 }
 
 impl Container {
-    /// Creates a new [`Container`] widget.
+    /// Creates a new [`Container`] widget with the specified background, padding and border, which
+    /// are kept when it's restyled. The border is its width and color.
     pub fn new(direction: Direction, align: Align, gap: f32, bg: Color, padding: Option<(f32, f32, f32, f32)>, border: Option<(f32, Color)>) -> Self {
+        let (border_width, border_color) = border.unwrap_or((0.0, BLANK));
+        Self::themed(direction, align, gap).with_style(StyleOverride {
+            bg: Some(bg),
+            fg: border.map(|_| border_color),
+            border_width: Some(border_width),
+            padding: Some(padding.unwrap_or((0.0, 0.0, 0.0, 0.0))),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new [`Container`] widget styled by the global [`Theme`]. Its border is drawn in
+    /// the foreground color.
+    pub fn themed(direction: Direction, align: Align, gap: f32) -> Self {
         Self {
             direction,
             align,
             gap,
+            line_gap: gap,
             children: Vec::new(),
-            bg,
-            padding,
-            border,
+            flex: Vec::new(),
+            justify: Justify::Start,
+            constraints: Constraints::unbounded(),
+            style: Theme::global().container,
+            overrides: StyleOverride::new(),
+            theme: None,
+            enabled: true,
+            inherited_enabled: true,
+            id: None,
+            size: None,
+            intrinsic: Cell::new(None),
+            rects: Vec::new(),
+            laid_out: false,
+        }
+    }
+
+    /// Returns a [`ContainerBuilder`] for an empty vertical container which aligns its children
+    /// to the start, without gaps, styled by the global [`Theme`].
+    pub fn builder() -> ContainerBuilder {
+        ContainerBuilder {
+            container: Container::themed(Direction::Vertical, Align::Start, 0.0),
+            line_gap: None,
         }
     }
-    
+
+    /// Overrides part of the style of the [`Container`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self.invalidate();
+        self
+    }
+
+    /// Gives the [`Container`] its own [`Theme`], and restyles it and its children with it. The
+    /// theme is passed down to the children instead of the one the container is restyled with,
+    /// and to children added later.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = None;
+        self.apply_theme(&theme);
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Enables or disables the [`Container`] widget, see [`Container::set_enabled`].
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.set_enabled(enabled);
+        self
+    }
+
+    /// Enables or disables the [`Container`] widget. Every widget in a disabled container is
+    /// disabled too, whether it's enabled itself or not, including the ones added later.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut form = Container::builder()
+    ///     .child(Box::new(TextInput::builder().id("name".to_string()).build()))
+    ///     .child(Box::new(Button::builder().id("send".to_string()).build()))
+    ///     .build();
+    /// form.set_enabled(false);
+    /// assert!(!form.find::<Button>("send").unwrap().is_enabled());
+    /// assert!(!form.focus("name"));
+    ///
+    /// let mut input = ScriptedInput::new();
+    /// input.move_mouse(100.0, 75.0).click(MouseButton::Left);
+    /// while !input.is_finished() {
+    ///     form.update(0.0, 0.0, &mut input.poll());
+    ///     assert_eq!(form.take_events(), vec![]);
+    /// }
+    ///
+    /// form.set_enabled(true);
+    /// assert!(form.focus("name"));
+    /// ```
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        let enabled = self.is_enabled();
+        for child in self.children_mut() {
+            child.set_inherited_enabled(enabled);
+        }
+    }
+
+    /// Sets the ID of the [`Container`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets how the children are spread along the main axis when the container is bigger than
+    /// they are.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut container = Container::new(Direction::Horizontal, Align::Start, 0.0, BLACK, None, None)
+    ///     .with_justify(Justify::SpaceBetween)
+    ///     .with_width(300.0);
+    /// for _ in 0..3 {
+    ///     container.add_child(Box::new(Button::new(50.0, 20.0, String::new(), BLACK, WHITE, None)));
+    /// }
+    ///
+    /// container.layout(Constraints::unbounded());
+    /// let xs = (0..3).map(|i| container.child_rect(i).unwrap().x).collect::<Vec<_>>();
+    /// assert_eq!(xs, vec![0.0, 125.0, 250.0]);
+    /// ```
+    pub fn with_justify(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    /// Sets the gap between the lines of a wrapping container, which is the same as the gap
+    /// between children by default.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut tags = Container::new(Direction::HorizontalWrap, Align::Start, 10.0, BLACK, None, None)
+    ///     .with_line_gap(5.0)
+    ///     .with_max_size(200.0, f32::INFINITY);
+    /// for _ in 0..3 {
+    ///     tags.add_child(Box::new(Button::new(80.0, 20.0, String::new(), BLACK, WHITE, None)));
+    /// }
+    ///
+    /// assert_eq!(tags.layout(Constraints::unbounded()), Size::new(170.0, 45.0));
+    /// assert_eq!(tags.child_rect(2), Some(Rect::new(0.0, 25.0, 80.0, 20.0)));
+    /// ```
+    pub fn with_line_gap(mut self, line_gap: f32) -> Self {
+        self.line_gap = line_gap;
+        self.invalidate();
+        self
+    }
+
+    /// Makes the container exactly `width` wide, whatever its children need.
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.constraints.min_width = width;
+        self.constraints.max_width = width;
+        self.invalidate();
+        self
+    }
+
+    /// Makes the container exactly `height` tall, whatever its children need.
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.constraints.min_height = height;
+        self.constraints.max_height = height;
+        self.invalidate();
+        self
+    }
+
+    /// Keeps the container from getting smaller than `width` and `height`.
+    pub fn with_min_size(mut self, width: f32, height: f32) -> Self {
+        self.constraints.min_width = width;
+        self.constraints.min_height = height;
+        self.invalidate();
+        self
+    }
+
+    /// Keeps the container from getting bigger than `width` and `height`, children which don't
+    /// fit overflow it unless they shrink.
+    pub fn with_max_size(mut self, width: f32, height: f32) -> Self {
+        self.constraints.max_width = width;
+        self.constraints.max_height = height;
+        self.invalidate();
+        self
+    }
+
     /// Adds a child [`Widget`] to the container.
     pub fn add_child(&mut self, child: Box<dyn Widget>) {
+        self.add_flex_child(child, Flex::default());
+    }
+
+    /// Adds a child [`Widget`] to the container which grows or shrinks along the main axis as
+    /// specified by its [`Flex`].
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut toolbar = Container::new(Direction::Horizontal, Align::Stretch, 0.0, BLACK, None, None)
+    ///     .with_width(400.0);
+    /// toolbar.add_child(Box::new(Button::new(100.0, 40.0, "Back".to_string(), BLACK, WHITE, None)));
+    /// toolbar.add_flex_child(Box::new(TextInput::new(100.0, 30.0, BLACK, WHITE, None)), Flex::grow(1.0));
+    ///
+    /// toolbar.layout(Constraints::unbounded());
+    /// assert_eq!(toolbar.child_rect(1), Some(Rect::new(100.0, 0.0, 300.0, 40.0)));
+    /// assert_eq!(toolbar.get_child(1).unwrap().width(), 300.0);
+    /// ```
+    pub fn add_flex_child(&mut self, mut child: Box<dyn Widget>, flex: Flex) {
+        if let Some(theme) = &self.theme {
+            child.apply_theme(theme);
+        }
+        child.set_inherited_enabled(self.is_enabled());
+        self.invalidate();
         self.children.push(child);
+        self.flex.push(flex);
+    }
+
+    /// Sets how the child at the specified index grows or shrinks along the main axis.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set_flex(&mut self, index: usize, flex: Flex) {
+        self.invalidate();
+        self.flex[index] = flex;
     }
 
     /// Gets a child [`Widget`] from the container.
-    pub fn get_child(&self, index: usize) -> Option<&Box<dyn Widget>> {
-        self.children.get(index)
+    pub fn get_child(&self, index: usize) -> Option<&dyn Widget> {
+        self.children.get(index).map(|child| child.as_ref())
     }
 
     /// Gets a child [`Widget`] from the container and downcasts it to the specified type.
@@ -69,139 +370,593 @@ impl Container {
             .as_any()
             .downcast_ref::<T>()
     }
-}
 
-impl Widget for Container {
-    fn as_any(&self) -> &dyn Any {
-        self
+    /// Gets a mutable child [`Widget`] from the container.
+    pub fn get_child_mut(&mut self, index: usize) -> Option<&mut dyn Widget> {
+        self.children.get_mut(index).map(|child| child.as_mut())
     }
 
-    fn width(&self) -> f32 {
-        let base_width = match self.direction {
-            Direction::Horizontal => {
-                let mut width = 0.0;
-                for child in &self.children {
-                    width += child.width() + self.gap;
-                }
-                width - self.gap
-            }
-            Direction::Vertical => self.children.iter().map(|c| c.width()).fold(0.0, f32::max),
-        };
+    /// Gets a mutable child [`Widget`] from the container and downcasts it to the specified type.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut container = Container::new(Direction::Vertical, Align::Start, 0.0, BLACK, None, None);
+    /// container.add_child(Box::new(TextInput::new(200.0, 40.0, BLACK, WHITE, None)));
+    ///
+    /// container.get_child_as_mut::<TextInput>(0).unwrap().set_text("Hello".to_string());
+    /// assert_eq!(container.get_child_as::<TextInput>(0).unwrap().get_text(), "Hello");
+    /// ```
+    pub fn get_child_as_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
+        self.children.get_mut(index)?
+            .as_any_mut()
+            .downcast_mut::<T>()
+    }
+
+    /// Inserts a child [`Widget`] at the specified index, shifting the children after it.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of children.
+    pub fn insert_child(&mut self, index: usize, mut child: Box<dyn Widget>) {
+        if let Some(theme) = &self.theme {
+            child.apply_theme(theme);
+        }
+        child.set_inherited_enabled(self.is_enabled());
+        self.invalidate();
+        self.children.insert(index, child);
+        self.flex.insert(index, Flex::default());
+    }
 
-        if let Some((left, right, _, _)) = self.padding {
-            base_width + left + right
+    /// Removes the child [`Widget`] at the specified index and returns it.
+    pub fn remove_child(&mut self, index: usize) -> Option<Box<dyn Widget>> {
+        if index < self.children.len() {
+            self.invalidate();
+            self.flex.remove(index);
+            Some(self.children.remove(index))
         } else {
-            base_width
+            None
         }
     }
 
-    fn height(&self) -> f32 {
-        let base_height = match self.direction {
-            Direction::Horizontal => self.children.iter().map(|c| c.height()).fold(0.0, f32::max),
-            Direction::Vertical => {
-                let mut height = 0.0;
-                for child in &self.children {
-                    height += child.height() + self.gap;
-                }
-                height - self.gap
-            }
-        };
+    /// Swaps the children at the two specified indices.
+    ///
+    /// # Panics
+    /// Panics if either index is out of bounds.
+    pub fn swap_children(&mut self, a: usize, b: usize) {
+        self.invalidate();
+        self.children.swap(a, b);
+        self.flex.swap(a, b);
+    }
 
-        if let Some((_, _, top, bottom)) = self.padding {
-            base_height + top + bottom
-        } else {
-            base_height
+    /// Removes every child from the container.
+    pub fn clear(&mut self) {
+        self.invalidate();
+        self.children.clear();
+        self.flex.clear();
+    }
+
+    /// Returns the number of children in the container.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Returns if the container has no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Returns an iterator over the children of the container.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Widget> {
+        self.children.iter().map(|child| child.as_ref())
+    }
+
+    /// Returns a mutable iterator over the children of the container.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut dyn Widget> {
+        self.children.iter_mut().map(|child| child.as_mut())
+    }
+
+    /// Returns where the child at the specified index goes, relative to the top left of the
+    /// container, as worked out by the last [`Widget::layout`].
+    pub fn child_rect(&self, index: usize) -> Option<Rect> {
+        self.size.and(self.rects.get(index).copied())
+    }
+
+    fn padding(&self) -> (f32, f32, f32, f32) {
+        self.style.padding
+    }
+
+    fn is_horizontal(&self) -> bool {
+        matches!(self.direction, Direction::Horizontal | Direction::HorizontalWrap)
+    }
+
+    fn is_wrapping(&self) -> bool {
+        matches!(self.direction, Direction::HorizontalWrap | Direction::VerticalWrap)
+    }
+
+    /// Returns the size along the main axis.
+    fn main(&self, size: Size) -> f32 {
+        if self.is_horizontal() { size.width } else { size.height }
+    }
+
+    /// Returns the size along the cross axis.
+    fn cross(&self, size: Size) -> f32 {
+        if self.is_horizontal() { size.height } else { size.width }
+    }
+
+    /// Returns the [`Size`] with the specified main and cross axis sizes.
+    fn size_from_axes(&self, main: f32, cross: f32) -> Size {
+        if self.is_horizontal() { Size::new(main, cross) } else { Size::new(cross, main) }
+    }
+
+    /// Returns the size of the line of children with the specified sizes along both axes.
+    fn line_size(&self, sizes: &[Size]) -> (f32, f32) {
+        let gaps = self.gap * sizes.len().saturating_sub(1) as f32;
+        let main = sizes.iter().map(|s| self.main(*s)).sum::<f32>() + gaps;
+        let cross = sizes.iter().map(|s| self.cross(*s)).fold(0.0, f32::max);
+        (main, cross)
+    }
+
+    /// Splits the children of the specified sizes into lines no longer than `max_main`, there's
+    /// only one line unless the container wraps.
+    fn break_lines(&self, sizes: &[Size], max_main: f32) -> Vec<Range<usize>> {
+        let max_main = if self.is_wrapping() { max_main } else { f32::INFINITY };
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut main = 0.0;
+        for (i, size) in sizes.iter().enumerate() {
+            if i > start && main + self.gap + self.main(*size) > max_main {
+                lines.push(start..i);
+                start = i;
+            }
+            main = if i == start { self.main(*size) } else { main + self.gap + self.main(*size) };
+        }
+        if start < sizes.len() || lines.is_empty() {
+            lines.push(start..sizes.len());
         }
+        lines
     }
 
-    fn bg(&self) -> Color {
-        self.bg
+    /// Returns the size of the container, with padding, when its children have the specified
+    /// sizes and are split into the specified lines.
+    fn measure(&self, sizes: &[Size], lines: &[Range<usize>]) -> Size {
+        let line_sizes = lines.iter().map(|line| self.line_size(&sizes[line.clone()])).collect::<Vec<_>>();
+        let main = line_sizes.iter().map(|(main, _)| *main).fold(0.0, f32::max);
+        let cross = line_sizes.iter().map(|(_, cross)| cross).sum::<f32>() + self.line_gap * lines.len().saturating_sub(1) as f32;
+        let content = self.size_from_axes(main, cross);
+
+        let (pad_left, pad_right, pad_top, pad_bottom) = self.padding();
+        Size::new(content.width + pad_left + pad_right, content.height + pad_top + pad_bottom)
     }
 
-    fn update(&mut self, x: f32, y: f32) {
-        let (pad_left, pad_right, pad_top, pad_bottom) = self.padding.unwrap_or((0.0, 0.0, 0.0, 0.0));
+    /// Returns the size of the container without padding.
+    fn inner_size(&self, size: Size) -> Size {
+        let (pad_left, pad_right, pad_top, pad_bottom) = self.padding();
+        Size::new(size.width - pad_left - pad_right, size.height - pad_top - pad_bottom)
+    }
+
+    /// Returns the size along the cross axis which the children in a line are aligned in. A
+    /// container which doesn't wrap has a single line which fills it.
+    fn line_cross(&self, sizes: &[Size], inner: Size) -> f32 {
+        if self.is_wrapping() { self.line_size(sizes).1 } else { self.cross(inner) }
+    }
 
-        let container_width = self.width() - pad_left - pad_right;
-        let container_height = self.height() - pad_top - pad_bottom;
+    /// Shares out the space left along the main axis by the children of the specified sizes
+    /// according to their [`Flex`], returns their new sizes along the main axis.
+    fn flex_sizes(&self, sizes: &[Size], flex: &[Flex], main: f32) -> Vec<f32> {
+        let mut mains = sizes.iter().map(|s| self.main(*s)).collect::<Vec<_>>();
+        let free = main - self.line_size(sizes).0;
 
-        let mut x = x + pad_left;
-        let mut y = y + pad_top;
+        if free > 0.0 {
+            let total_grow = flex.iter().map(|f| f.grow).sum::<f32>();
+            if total_grow > 0.0 {
+                for (main, flex) in mains.iter_mut().zip(flex) {
+                    *main += free * flex.grow / total_grow;
+                }
+            }
+        } else if free < 0.0 {
+            let total_shrink = mains.iter().zip(flex).map(|(main, f)| main * f.shrink).sum::<f32>();
+            if total_shrink > 0.0 {
+                for (main, flex) in mains.iter_mut().zip(flex) {
+                    *main = (*main + free * *main * flex.shrink / total_shrink).max(0.0);
+                }
+            }
+        }
+        mains
+    }
 
-        for child in self.children.iter_mut() {
-            let child_width = child.width();
-            let child_height = child.height();
+    /// Places the lines of children of the specified sizes in a container of the specified size,
+    /// spreading the children in every line along the main axis and aligning them on the cross
+    /// axis.
+    fn arrange(&self, sizes: &[Size], lines: &[Range<usize>], size: Size) -> Vec<Rect> {
+        let (pad_left, _, pad_top, _) = self.padding();
+        let inner = self.inner_size(size);
+        let container_main = self.main(inner);
 
-            let offset_x = match self.direction {
-                Direction::Horizontal => 0.0,
-                Direction::Vertical => match self.align {
-                    Align::Start => 0.0,
-                    Align::Center => (container_width - child_width) / 2.0,
-                    Align::End => container_width - child_width,
-                },
-            };
+        let mut rects = Vec::with_capacity(sizes.len());
+        let mut line_start = 0.0;
+        for line in lines {
+            let sizes = &sizes[line.clone()];
+            let line_cross = self.line_cross(sizes, inner);
 
-            let offset_y = match self.direction {
-                Direction::Horizontal => match self.align {
-                    Align::Start => 0.0,
-                    Align::Center => (container_height - child_height) / 2.0,
-                    Align::End => container_height - child_height,
-                },
-                Direction::Vertical => 0.0,
+            let count = sizes.len() as f32;
+            let leftover = container_main - self.line_size(sizes).0;
+            let spread = leftover.max(0.0);
+            let (mut main, spacing) = match self.justify {
+                Justify::Start => (0.0, 0.0),
+                Justify::Center => (leftover / 2.0, 0.0),
+                Justify::End => (leftover, 0.0),
+                Justify::SpaceBetween if sizes.len() > 1 => (0.0, spread / (count - 1.0)),
+                Justify::SpaceBetween => (0.0, 0.0),
+                Justify::SpaceAround => (spread / count / 2.0, spread / count),
+                Justify::SpaceEvenly => (spread / (count + 1.0), spread / (count + 1.0)),
             };
 
-            // Apply alignment offsets
-            child.update(x + offset_x, y + offset_y);
+            for child in sizes {
+                let cross = line_start + match self.align {
+                    Align::Start | Align::Stretch => 0.0,
+                    Align::Center => (line_cross - self.cross(*child)) / 2.0,
+                    Align::End => line_cross - self.cross(*child),
+                };
 
-            match self.direction {
-                Direction::Horizontal => x += child_width + self.gap,
-                Direction::Vertical => y += child_height + self.gap,
+                let offset = self.size_from_axes(main, cross);
+                rects.push(Rect::new(pad_left + offset.width, pad_top + offset.height, child.width, child.height));
+                main += self.main(*child) + self.gap + spacing;
             }
+            line_start += line_cross + self.line_gap;
         }
+        rects
     }
 
-    fn render(&self, x: f32, y: f32) {
-        let (pad_left, pad_right, pad_top, pad_bottom) = self.padding.unwrap_or((0.0, 0.0, 0.0, 0.0));
-        let padded_x = x + pad_left;
-        let padded_y = y + pad_top;
-        let original_x = x;
-        let original_y = y;
-        let width = self.width();
-        let height = self.height();
+    /// Forgets the size from the last layout and the intrinsic size, after something which
+    /// changes them.
+    fn invalidate(&mut self) {
+        self.size = None;
+        self.intrinsic.set(None);
+        self.laid_out = false;
+    }
 
-        draw_rectangle(x, y, width, height, self.bg);
+    /// Returns the size the container takes up without laying it out, which is only measured
+    /// once until its children change.
+    fn intrinsic_size(&self) -> Size {
+        if let Some(size) = self.intrinsic.get() {
+            return size;
+        }
+        let size = self.unlaid().0;
+        self.intrinsic.set(Some(size));
+        size
+    }
 
-        let mut x = padded_x;
-        let mut y = padded_y;
+    /// Returns the size and child rects of the container without laying it out, from the
+    /// current sizes of its children.
+    fn unlaid(&self) -> (Size, Vec<Rect>) {
+        let sizes = intrinsic_sizes(&self.children);
+        let max_main = self.main(Size::new(self.constraints.max_width, self.constraints.max_height));
+        let lines = self.break_lines(&sizes, max_main);
+        let size = self.constraints.constrain(self.measure(&sizes, &lines));
+        let rects = self.arrange(&sizes, &lines, size);
+        (size, rects)
+    }
 
-        for child in &self.children {
-            let offset_x = match self.direction {
-                Direction::Horizontal => 0.0,
-                Direction::Vertical => match self.align {
-                    Align::Start => 0.0,
-                    Align::Center => (self.width() - pad_left - pad_right - child.width()) / 2.0,
-                    Align::End => self.width() - pad_left - pad_right - child.width(),
-                },
-            };
+    /// Finds a descendant [`Widget`] by its ID path, like `"login.password"`, and downcasts it to
+    /// the specified type. Nested containers are searched recursively.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut login = Container::new(Direction::Vertical, Align::Start, 0.0, BLACK, None, None)
+    ///     .with_id("login".to_string());
+    /// login.add_child(Box::new(TextInput::new(200.0, 40.0, BLACK, WHITE, None).with_id("password".to_string())));
+    /// let mut root = Container::new(Direction::Vertical, Align::Start, 0.0, BLACK, None, None);
+    /// root.add_child(Box::new(login));
+    ///
+    /// root.find_mut::<TextInput>("login.password").unwrap().set_text("hunter2".to_string());
+    /// assert_eq!(root.find::<TextInput>("password").unwrap().get_text(), "hunter2");
+    /// ```
+    pub fn find<T: 'static>(&self, path: &str) -> Option<&T> {
+        (self as &dyn Widget).find::<T>(path)
+    }
 
-            let offset_y = match self.direction {
-                Direction::Horizontal => match self.align {
-                    Align::Start => 0.0,
-                    Align::Center => (self.height() - pad_top - pad_bottom - child.height()) / 2.0,
-                    Align::End => self.height() - pad_top - pad_bottom - child.height(),
-                },
-                Direction::Vertical => 0.0,
-            };
+    /// Finds a descendant [`Widget`] by its ID path and mutably downcasts it to the specified
+    /// type. See [`Container::find`].
+    pub fn find_mut<T: 'static>(&mut self, path: &str) -> Option<&mut T> {
+        (self as &mut dyn Widget).find_mut::<T>(path)
+    }
+
+    /// Returns the descendant [`Widget`] which has the keyboard focus, if there is one.
+    pub fn focused_widget(&self) -> Option<&dyn Widget> {
+        (self as &dyn Widget).focused_widget()
+    }
+
+    /// Gives the keyboard focus to the descendant [`Widget`] with the specified ID path, and takes
+    /// it away from every other one. Returns false if there's no such widget or it can't take the
+    /// focus. See [`FocusManager`](crate::focus::FocusManager).
+    pub fn focus(&mut self, path: &str) -> bool {
+        (self as &mut dyn Widget).focus(path)
+    }
+
+    /// Takes every [`Event`] queued by the widgets in the container during the last update, in
+    /// tree order.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut container = Container::new(Direction::Vertical, Align::Start, 0.0, BLACK, None, None);
+    /// container.add_child(Box::new(Button::new(100.0, 40.0, "Play".to_string(), BLACK, WHITE, None).with_id("play".to_string())));
+    ///
+    /// let mut input = ScriptedInput::new();
+    /// input.move_mouse(50.0, 20.0).click(MouseButton::Left);
+    /// container.update(0.0, 0.0, &mut input.poll());
+    /// container.update(0.0, 0.0, &mut input.poll());
+    /// container.update(0.0, 0.0, &mut input.poll());
+    ///
+    /// assert_eq!(container.take_events(), vec![Event::Clicked { id: Some("play".to_string()) }]);
+    /// ```
+    pub fn take_events(&mut self) -> Vec<Event> {
+        (self as &mut dyn Widget).take_events()
+    }
+}
 
-            child.render(x + offset_x, y + offset_y);
+/// The [`ContainerBuilder`] struct makes a [`Container`] one setting at a time, made with
+/// [`Container::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut menu = Container::builder()
+///     .align(Align::Center)
+///     .gap(10.0)
+///     .padding((5.0, 5.0, 5.0, 5.0))
+///     .border(2.0, WHITE)
+///     .child(Box::new(Button::builder().size(100.0, 40.0).text("Play".to_string()).build()))
+///     .child(Box::new(Button::builder().size(60.0, 40.0).text("Quit".to_string()).build()))
+///     .build();
+///
+/// assert_eq!(menu.layout(Constraints::unbounded()), Size::new(110.0, 100.0));
+/// assert_eq!(menu.child_rect(1), Some(Rect::new(25.0, 55.0, 60.0, 40.0)));
+/// ```
+pub struct ContainerBuilder {
+    container: Container,
+    line_gap: Option<f32>,
+}
+
+impl ContainerBuilder {
+    /// Sets the [`Direction`] the children are placed in.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.container.direction = direction;
+        self
+    }
 
-            match self.direction {
-                Direction::Horizontal => x += child.width() + self.gap,
-                Direction::Vertical => y += child.height() + self.gap,
+    /// Sets how the children are aligned on the cross axis.
+    pub fn align(mut self, align: Align) -> Self {
+        self.container.align = align;
+        self
+    }
+
+    /// Sets how the children are spread along the main axis, see [`Container::with_justify`].
+    pub fn justify(mut self, justify: Justify) -> Self {
+        self.container = self.container.with_justify(justify);
+        self
+    }
+
+    /// Sets the space between children, and between lines of wrapped children unless a line
+    /// gap is set.
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.container.gap = gap;
+        self
+    }
+
+    /// Sets the space between lines of wrapped children.
+    pub fn line_gap(mut self, line_gap: f32) -> Self {
+        self.line_gap = Some(line_gap);
+        self
+    }
+
+    /// Sets the padding of the container, left, right, top and bottom.
+    pub fn padding(self, padding: (f32, f32, f32, f32)) -> Self {
+        self.style(StyleOverride::new().padding(padding))
+    }
+
+    /// Draws a border of the specified width and color around the container.
+    pub fn border(self, width: f32, color: Color) -> Self {
+        self.style(StyleOverride::new().border_width(width).fg(color))
+    }
+
+    /// Makes the container exactly `width` wide, see [`Container::with_width`].
+    pub fn width(mut self, width: f32) -> Self {
+        self.container = self.container.with_width(width);
+        self
+    }
+
+    /// Makes the container exactly `height` tall, see [`Container::with_height`].
+    pub fn height(mut self, height: f32) -> Self {
+        self.container = self.container.with_height(height);
+        self
+    }
+
+    /// Keeps the container at least `width` wide and `height` tall.
+    pub fn min_size(mut self, width: f32, height: f32) -> Self {
+        self.container = self.container.with_min_size(width, height);
+        self
+    }
+
+    /// Keeps the container at most `width` wide and `height` tall.
+    pub fn max_size(mut self, width: f32, height: f32) -> Self {
+        self.container = self.container.with_max_size(width, height);
+        self
+    }
+
+    /// Sets the ID of the container, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.container = self.container.with_id(id);
+        self
+    }
+
+    /// Enables or disables the container and every widget in it, see
+    /// [`Container::set_enabled`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.container = self.container.with_enabled(enabled);
+        self
+    }
+
+    /// Overrides part of the style of the container, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.container = self.container.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the container.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Gives the container its own [`Theme`], see [`Container::with_theme`].
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.container = self.container.with_theme(theme);
+        self
+    }
+
+    /// Adds a child [`Widget`] to the container.
+    pub fn child(mut self, child: Box<dyn Widget>) -> Self {
+        self.container.add_child(child);
+        self
+    }
+
+    /// Adds a child [`Widget`] to the container which grows or shrinks as specified by its
+    /// [`Flex`].
+    pub fn flex_child(mut self, child: Box<dyn Widget>, flex: Flex) -> Self {
+        self.container.add_flex_child(child, flex);
+        self
+    }
+
+    /// Makes the [`Container`].
+    pub fn build(mut self) -> Container {
+        self.container.line_gap = self.line_gap.unwrap_or(self.container.gap);
+        self.container
+    }
+}
+
+impl Widget for Container {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        self.iter().collect()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.iter_mut().collect()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled && self.inherited_enabled
+    }
+
+    fn set_inherited_enabled(&mut self, enabled: bool) {
+        self.inherited_enabled = enabled;
+        let enabled = self.is_enabled();
+        for child in self.children_mut() {
+            child.set_inherited_enabled(enabled);
+        }
+    }
+
+    fn width(&self) -> f32 {
+        self.size.unwrap_or_else(|| self.intrinsic_size()).width
+    }
+
+    fn height(&self) -> f32 {
+        self.size.unwrap_or_else(|| self.intrinsic_size()).height
+    }
+
+    fn bg(&self) -> Color {
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.invalidate();
+        let theme = self.theme.as_ref().unwrap_or(theme);
+        self.style = self.overrides.resolve(theme, &theme.container);
+        for child in &mut self.children {
+            child.apply_theme(theme);
+        }
+    }
+
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let constraints = self.constraints.enforce(constraints);
+        let (pad_left, pad_right, pad_top, pad_bottom) = self.padding();
+        let child_constraints = constraints.shrink(pad_left + pad_right, pad_top + pad_bottom).loosen();
+
+        let mut sizes = self.children.iter_mut()
+            .map(|child| child.layout(child_constraints))
+            .collect::<Vec<_>>();
+        let max_main = self.main(Size::new(child_constraints.max_width, child_constraints.max_height));
+        let lines = self.break_lines(&sizes, max_main);
+        let size = constraints.constrain(self.measure(&sizes, &lines));
+        let inner = self.inner_size(size);
+
+        // Lay out the children again at their final size if they grew, shrank or are stretched
+        let stretch = matches!(self.align, Align::Stretch);
+        for line in &lines {
+            let line_cross = self.line_cross(&sizes[line.clone()], inner);
+            let mains = self.flex_sizes(&sizes[line.clone()], &self.flex[line.clone()], self.main(inner));
+            for (i, main) in line.clone().zip(mains) {
+                if main == self.main(sizes[i]) && !stretch {
+                    continue;
+                }
+                let cross = if stretch { line_cross } else { self.cross(sizes[i]) };
+                let tight = self.size_from_axes(main, cross);
+                sizes[i] = self.children[i].layout(Constraints::tight(tight.width, tight.height));
             }
         }
 
-        if let Some((border_width, border_color)) = self.border {
-            draw_rectangle_lines(original_x, original_y, width, height, border_width, border_color);
+        self.rects = self.arrange(&sizes, &lines, size);
+        self.size = Some(size);
+        self.laid_out = true;
+        size
+    }
+
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        layout_unless_laid_out(self, |container| &mut container.laid_out);
+
+        // The children drawn last are on top, so they get the mouse first
+        for (child, rect) in self.children.iter_mut().zip(&self.rects).rev() {
+            child.update(x + rect.x, y + rect.y, input);
+        }
+        // A background hides whatever is under it from the mouse
+        if let Some(size) = self.size && self.style.bg.a > 0.0 && input.hit_test(x, y, size.width, size.height) {
+            input.consume_mouse();
+        }
+    }
+
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let unlaid;
+        let (size, rects) = match self.size {
+            Some(size) => (size, &self.rects),
+            None => {
+                unlaid = self.unlaid();
+                (unlaid.0, &unlaid.1)
+            }
+        };
+
+        self.style.draw_background(painter, x, y, size.width, size.height, self.style.bg);
+
+        for (child, rect) in self.children.iter().zip(rects) {
+            child.render(x + rect.x, y + rect.y, painter);
         }
+
+        self.style.draw_border(painter, x, y, size.width, size.height, self.style.fg);
     }
 }
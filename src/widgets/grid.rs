@@ -0,0 +1,621 @@
+//! This module defines the [`Grid`] widget, which arranges other widgets in rows and columns.
+//!
+//! This module contains the following:
+//! 1. The [`Grid`] struct, which places its children in the cells of a grid, it itself implements
+//!    the [`Widget`] trait.
+//! 2. The [`Track`] enum, which defines how wide a column or how tall a row is.
+//! 3. The [`GridCell`] struct, which defines the cells a child covers and how it's aligned in
+//!    them.
+//! 4. The [`GridBuilder`] struct, which makes a [`Grid`] one setting at a time.
+use std::any::Any;
+use std::cell::Cell;
+
+use macroquad::prelude::*;
+
+use crate::input::InputState;
+use crate::layout::{intrinsic_sizes, layout_unless_laid_out, Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::{Style, StyleOverride, Theme};
+
+use super::container::Align;
+use super::widget::Widget;
+
+/// The [`Track`] enum defines how wide a column or how tall a row of a [`Grid`] is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Track {
+    /// The track is exactly this wide or tall.
+    Fixed(f32),
+    /// The track is as wide or tall as the biggest child in it.
+    Auto,
+    /// The track takes this share of the space left by the other tracks. When the grid has no
+    /// size limit, the track is as big as its biggest child needs it to be instead.
+    Fraction(f32),
+}
+
+/// The [`GridCell`] struct defines where a child of a [`Grid`] goes: the column and row it starts
+/// in, how many columns and rows it spans and how it's aligned within them.
+///
+/// Children are aligned to the top left of their cells by default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridCell {
+    pub column: usize,
+    pub row: usize,
+    pub column_span: usize,
+    pub row_span: usize,
+    pub align_x: Align,
+    pub align_y: Align,
+}
+
+impl GridCell {
+    /// Creates a new [`GridCell`] for the specified column and row.
+    pub fn new(column: usize, row: usize) -> Self {
+        Self {
+            column,
+            row,
+            column_span: 1,
+            row_span: 1,
+            align_x: Align::Start,
+            align_y: Align::Start,
+        }
+    }
+
+    /// Makes the child span the specified number of columns and rows.
+    pub fn span(mut self, columns: usize, rows: usize) -> Self {
+        self.column_span = columns.max(1);
+        self.row_span = rows.max(1);
+        self
+    }
+
+    /// Sets how the child is aligned horizontally and vertically within its cells.
+    pub fn align(mut self, align_x: Align, align_y: Align) -> Self {
+        self.align_x = align_x;
+        self.align_y = align_y;
+        self
+    }
+}
+
+/// Works out the size of every track from the `(first track, span, size)` of the children in
+/// them. Children which don't fit in the tracks they span make the non-fixed tracks bigger, and
+/// fractional tracks share the space left in `available`, if it's finite.
+fn size_tracks(tracks: &[Track], needs: &[(usize, usize, f32)], available: f32, gap: f32) -> Vec<f32> {
+    let count = needs.iter().map(|(start, span, _)| start + span).fold(tracks.len(), usize::max);
+    let track = |i: usize| tracks.get(i).copied().unwrap_or(Track::Auto);
+
+    let mut sizes = (0..count)
+        .map(|i| match track(i) {
+            Track::Fixed(size) => size,
+            _ => 0.0,
+        })
+        .collect::<Vec<_>>();
+
+    for (start, _, size) in needs.iter().filter(|(_, span, _)| *span == 1) {
+        if !matches!(track(*start), Track::Fixed(_)) {
+            sizes[*start] = sizes[*start].max(*size);
+        }
+    }
+    for (start, span, size) in needs.iter().filter(|(_, span, _)| *span > 1) {
+        let end = start + span;
+        let current = sizes[*start..end].iter().sum::<f32>() + gap * (span - 1) as f32;
+        let flexible = (*start..end).filter(|i| !matches!(track(*i), Track::Fixed(_))).collect::<Vec<_>>();
+        if *size > current && !flexible.is_empty() {
+            for i in &flexible {
+                sizes[*i] += (size - current) / flexible.len() as f32;
+            }
+        }
+    }
+
+    let fractions = (0..count).filter_map(|i| match track(i) {
+        Track::Fraction(fraction) => Some((i, fraction)),
+        _ => None,
+    }).collect::<Vec<_>>();
+    let total_fraction = fractions.iter().map(|(_, fraction)| fraction).sum::<f32>();
+    if total_fraction > 0.0 {
+        let unit = if available.is_finite() {
+            let used = (0..count)
+                .filter(|i| !matches!(track(*i), Track::Fraction(_)))
+                .map(|i| sizes[i])
+                .sum::<f32>() + gap * count.saturating_sub(1) as f32;
+            (available - used).max(0.0) / total_fraction
+        } else {
+            fractions.iter().map(|(i, fraction)| sizes[*i] / fraction).fold(0.0, f32::max)
+        };
+        for (i, fraction) in fractions {
+            sizes[i] = unit * fraction;
+        }
+    }
+
+    sizes
+}
+
+/// Returns where every track starts.
+fn track_starts(sizes: &[f32], start: f32, gap: f32) -> Vec<f32> {
+    sizes.iter()
+        .scan(start, |position, size| {
+            let start = *position;
+            *position += size + gap;
+            Some(start)
+        })
+        .collect()
+}
+
+/// Returns the size of `span` tracks from `start`, with the gaps between them.
+fn span_size(sizes: &[f32], start: usize, span: usize, gap: f32) -> f32 {
+    sizes[start..start + span].iter().sum::<f32>() + gap * (span - 1) as f32
+}
+
+/// Returns the offset of a child of size `child` aligned in a cell of size `cell`.
+fn align_offset(align: Align, cell: f32, child: f32) -> f32 {
+    match align {
+        Align::Start | Align::Stretch => 0.0,
+        Align::Center => (cell - child) / 2.0,
+        Align::End => cell - child,
+    }
+}
+
+/// The [`Grid`] struct places other widgets in the cells of a grid of columns and rows, it itself
+/// implements the [`Widget`] trait, so it can be nested in a
+/// [`Container`](super::container::Container) and the other way around.
+///
+/// Children can be put in any cell, and span several columns and rows. Columns and rows which
+/// are used by a child but have no [`Track`] are [`Track::Auto`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut grid = Grid::new(
+///     vec![Track::Fixed(80.0), Track::Fraction(1.0)], vec![Track::Auto, Track::Auto],
+///     10.0, 5.0, BLACK, None, None,
+/// );
+/// grid.add_child(Box::new(Button::new(60.0, 30.0, "Name".to_string(), BLACK, WHITE, None)), GridCell::new(0, 0));
+/// grid.add_child(Box::new(TextInput::new(100.0, 40.0, BLACK, WHITE, None)), GridCell::new(1, 0).align(Align::Stretch, Align::Start));
+/// grid.add_child(Box::new(Button::new(100.0, 30.0, "Save".to_string(), BLACK, WHITE, None)), GridCell::new(0, 1).span(2, 1).align(Align::Center, Align::Start));
+///
+/// let size = grid.layout(Constraints::loose(400.0, 300.0));
+/// assert_eq!(size, Size::new(400.0, 75.0));
+/// assert_eq!(grid.child_rect(0), Some(Rect::new(0.0, 0.0, 60.0, 30.0)));
+/// assert_eq!(grid.child_rect(1), Some(Rect::new(90.0, 0.0, 310.0, 40.0)));
+/// assert_eq!(grid.child_rect(2), Some(Rect::new(150.0, 45.0, 100.0, 30.0)));
+/// ```
+pub struct Grid {
+    columns: Vec<Track>,
+    rows: Vec<Track>,
+    column_gap: f32,
+    row_gap: f32,
+    children: Vec<Box<dyn Widget>>,
+    cells: Vec<GridCell>,
+    style: Style,
+    overrides: StyleOverride,
+    id: Option<String>,
+    /// The size from the last layout, if the grid was laid out since its children changed.
+    size: Option<Size>,
+    /// The size the grid takes up before it's laid out, measured once since its children
+    /// changed, as it's measured from the whole subtree.
+    intrinsic: Cell<Option<Size>>,
+    /// Where every child goes, relative to the grid, from the last layout.
+    rects: Vec<Rect>,
+    /// Whether the grid was laid out since the last update, and hasn't changed since.
+    laid_out: bool,
+}
+
+impl Grid {
+    /// Creates a new [`Grid`] widget with the specified background, padding and border, which are
+    /// kept when it's restyled. The border is its width and color.
+    pub fn new(columns: Vec<Track>, rows: Vec<Track>, column_gap: f32, row_gap: f32, bg: Color, padding: Option<(f32, f32, f32, f32)>, border: Option<(f32, Color)>) -> Self {
+        let (border_width, border_color) = border.unwrap_or((0.0, BLANK));
+        Self::themed(columns, rows, column_gap, row_gap).with_style(StyleOverride {
+            bg: Some(bg),
+            fg: border.map(|_| border_color),
+            border_width: Some(border_width),
+            padding: Some(padding.unwrap_or((0.0, 0.0, 0.0, 0.0))),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new [`Grid`] widget styled by the global [`Theme`]. Its border is drawn in the
+    /// foreground color.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let mut theme = Theme::default();
+    /// theme.grid.padding = (10.0, 10.0, 10.0, 10.0);
+    ///
+    /// let mut grid = Grid::themed(vec![Track::Auto], vec![Track::Auto], 0.0, 0.0);
+    /// grid.add_child(Box::new(Button::themed(60.0, 30.0, "Ok".to_string())), GridCell::new(0, 0));
+    /// grid.apply_theme(&theme);
+    ///
+    /// assert_eq!(grid.layout(Constraints::unbounded()), Size::new(80.0, 50.0));
+    /// assert_eq!(grid.child_rect(0), Some(Rect::new(10.0, 10.0, 60.0, 30.0)));
+    /// ```
+    pub fn themed(columns: Vec<Track>, rows: Vec<Track>, column_gap: f32, row_gap: f32) -> Self {
+        Self {
+            columns,
+            rows,
+            column_gap,
+            row_gap,
+            children: Vec::new(),
+            cells: Vec::new(),
+            style: Theme::global().grid,
+            overrides: StyleOverride::new(),
+            id: None,
+            size: None,
+            intrinsic: Cell::new(None),
+            rects: Vec::new(),
+            laid_out: false,
+        }
+    }
+
+    /// Returns a [`GridBuilder`] for an empty grid without tracks or gaps, so every column and
+    /// row is [`Track::Auto`], styled by the global [`Theme`].
+    pub fn builder() -> GridBuilder {
+        GridBuilder {
+            grid: Grid::themed(Vec::new(), Vec::new(), 0.0, 0.0),
+        }
+    }
+
+    /// Overrides part of the style of the [`Grid`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self.invalidate();
+        self
+    }
+
+    /// Sets the ID of the [`Grid`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Adds a child [`Widget`] to the grid in the specified [`GridCell`].
+    pub fn add_child(&mut self, child: Box<dyn Widget>, cell: GridCell) {
+        self.invalidate();
+        self.children.push(child);
+        self.cells.push(cell);
+    }
+
+    /// Gets a child [`Widget`] from the grid, in the order they were added.
+    pub fn get_child(&self, index: usize) -> Option<&dyn Widget> {
+        self.children.get(index).map(|child| child.as_ref())
+    }
+
+    /// Gets a child [`Widget`] from the grid and downcasts it to the specified type.
+    pub fn get_child_as<T: 'static>(&self, index: usize) -> Option<&T> {
+        self.children.get(index)?
+            .as_any()
+            .downcast_ref::<T>()
+    }
+
+    /// Gets a mutable child [`Widget`] from the grid and downcasts it to the specified type.
+    pub fn get_child_as_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
+        self.children.get_mut(index)?
+            .as_any_mut()
+            .downcast_mut::<T>()
+    }
+
+    /// Removes the child [`Widget`] at the specified index and returns it.
+    pub fn remove_child(&mut self, index: usize) -> Option<Box<dyn Widget>> {
+        if index < self.children.len() {
+            self.invalidate();
+            self.cells.remove(index);
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Moves the child at the specified index to another [`GridCell`].
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set_cell(&mut self, index: usize, cell: GridCell) {
+        self.invalidate();
+        self.cells[index] = cell;
+    }
+
+    /// Removes every child from the grid.
+    pub fn clear(&mut self) {
+        self.invalidate();
+        self.children.clear();
+        self.cells.clear();
+    }
+
+    /// Returns the number of children in the grid.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Returns if the grid has no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Returns where the child at the specified index goes, relative to the top left of the grid,
+    /// as worked out by the last [`Widget::layout`].
+    pub fn child_rect(&self, index: usize) -> Option<Rect> {
+        self.size.and(self.rects.get(index).copied())
+    }
+
+    /// Finds a descendant [`Widget`] by its ID path and downcasts it to the specified type. See
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn find<T: 'static>(&self, path: &str) -> Option<&T> {
+        (self as &dyn Widget).find::<T>(path)
+    }
+
+    /// Finds a descendant [`Widget`] by its ID path and mutably downcasts it to the specified
+    /// type.
+    pub fn find_mut<T: 'static>(&mut self, path: &str) -> Option<&mut T> {
+        (self as &mut dyn Widget).find_mut::<T>(path)
+    }
+
+    fn padding(&self) -> (f32, f32, f32, f32) {
+        self.style.padding
+    }
+
+    fn size_columns(&self, sizes: &[Size], available: f32) -> Vec<f32> {
+        let needs = self.cells.iter().zip(sizes)
+            .map(|(cell, size)| (cell.column, cell.column_span, size.width))
+            .collect::<Vec<_>>();
+        size_tracks(&self.columns, &needs, available, self.column_gap)
+    }
+
+    fn size_rows(&self, sizes: &[Size], available: f32) -> Vec<f32> {
+        let needs = self.cells.iter().zip(sizes)
+            .map(|(cell, size)| (cell.row, cell.row_span, size.height))
+            .collect::<Vec<_>>();
+        size_tracks(&self.rows, &needs, available, self.row_gap)
+    }
+
+    /// Returns the area covered by every child's cells, relative to the grid.
+    fn cell_rects(&self, columns: &[f32], rows: &[f32]) -> Vec<Rect> {
+        let (pad_left, _, pad_top, _) = self.padding();
+        let xs = track_starts(columns, pad_left, self.column_gap);
+        let ys = track_starts(rows, pad_top, self.row_gap);
+        self.cells.iter()
+            .map(|cell| Rect::new(
+                xs[cell.column],
+                ys[cell.row],
+                span_size(columns, cell.column, cell.column_span, self.column_gap),
+                span_size(rows, cell.row, cell.row_span, self.row_gap),
+            ))
+            .collect()
+    }
+
+    /// Returns the size of the grid, with padding, for the specified track sizes.
+    fn measure(&self, columns: &[f32], rows: &[f32]) -> Size {
+        let (pad_left, pad_right, pad_top, pad_bottom) = self.padding();
+        let width = columns.iter().sum::<f32>() + self.column_gap * columns.len().saturating_sub(1) as f32;
+        let height = rows.iter().sum::<f32>() + self.row_gap * rows.len().saturating_sub(1) as f32;
+        Size::new(width + pad_left + pad_right, height + pad_top + pad_bottom)
+    }
+
+    /// Forgets the size from the last layout and the intrinsic size, after something which
+    /// changes them.
+    fn invalidate(&mut self) {
+        self.size = None;
+        self.intrinsic.set(None);
+        self.laid_out = false;
+    }
+
+    /// Returns the size the grid takes up without laying it out, which is only measured once
+    /// until its children change.
+    fn intrinsic_size(&self) -> Size {
+        if let Some(size) = self.intrinsic.get() {
+            return size;
+        }
+        let size = self.unlaid().0;
+        self.intrinsic.set(Some(size));
+        size
+    }
+
+    /// Returns the size and child rects of the grid without laying it out, from the current
+    /// sizes of its children.
+    fn unlaid(&self) -> (Size, Vec<Rect>) {
+        let sizes = intrinsic_sizes(&self.children);
+        let columns = self.size_columns(&sizes, f32::INFINITY);
+        let rows = self.size_rows(&sizes, f32::INFINITY);
+        let rects = self.arrange(&sizes, &self.cell_rects(&columns, &rows));
+        (self.measure(&columns, &rows), rects)
+    }
+
+    /// Aligns children of the specified sizes in their cells.
+    fn arrange(&self, sizes: &[Size], cells: &[Rect]) -> Vec<Rect> {
+        self.cells.iter().zip(sizes).zip(cells)
+            .map(|((cell, size), area)| Rect::new(
+                area.x + align_offset(cell.align_x, area.w, size.width),
+                area.y + align_offset(cell.align_y, area.h, size.height),
+                size.width,
+                size.height,
+            ))
+            .collect()
+    }
+}
+
+/// The [`GridBuilder`] struct makes a [`Grid`] one setting at a time, made with
+/// [`Grid::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut grid = Grid::builder()
+///     .columns(vec![Track::Fixed(80.0), Track::Auto])
+///     .gap(10.0, 5.0)
+///     .child(Box::new(Toggle::builder().size(60.0, 30.0).build()), GridCell::new(0, 0))
+///     .child(Box::new(TextInput::builder().size(100.0, 40.0).build()), GridCell::new(1, 0))
+///     .build();
+///
+/// grid.layout(Constraints::unbounded());
+/// assert_eq!(grid.child_rect(1), Some(Rect::new(90.0, 0.0, 100.0, 40.0)));
+/// ```
+pub struct GridBuilder {
+    grid: Grid,
+}
+
+impl GridBuilder {
+    /// Sets how wide every column is.
+    pub fn columns(mut self, columns: Vec<Track>) -> Self {
+        self.grid.columns = columns;
+        self
+    }
+
+    /// Sets how tall every row is.
+    pub fn rows(mut self, rows: Vec<Track>) -> Self {
+        self.grid.rows = rows;
+        self
+    }
+
+    /// Sets the space between columns and between rows.
+    pub fn gap(mut self, column_gap: f32, row_gap: f32) -> Self {
+        self.grid.column_gap = column_gap;
+        self.grid.row_gap = row_gap;
+        self
+    }
+
+    /// Overrides part of the style of the grid, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.grid = self.grid.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the grid.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Sets the padding of the grid, left, right, top and bottom.
+    pub fn padding(self, padding: (f32, f32, f32, f32)) -> Self {
+        self.style(StyleOverride::new().padding(padding))
+    }
+
+    /// Draws a border of the specified width and color around the grid.
+    pub fn border(self, width: f32, color: Color) -> Self {
+        self.style(StyleOverride::new().border_width(width).fg(color))
+    }
+
+    /// Sets the ID of the grid, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.grid = self.grid.with_id(id);
+        self
+    }
+
+    /// Adds a child [`Widget`] to the grid in the specified [`GridCell`].
+    pub fn child(mut self, child: Box<dyn Widget>, cell: GridCell) -> Self {
+        self.grid.add_child(child, cell);
+        self
+    }
+
+    /// Makes the [`Grid`].
+    pub fn build(self) -> Grid {
+        self.grid
+    }
+}
+
+impl Widget for Grid {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        self.children.iter().map(|child| child.as_ref()).collect()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.children.iter_mut().map(|child| child.as_mut()).collect()
+    }
+
+    fn width(&self) -> f32 {
+        self.size.unwrap_or_else(|| self.intrinsic_size()).width
+    }
+
+    fn height(&self) -> f32 {
+        self.size.unwrap_or_else(|| self.intrinsic_size()).height
+    }
+
+    fn bg(&self) -> Color {
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.grid);
+        self.invalidate();
+        for child in &mut self.children {
+            child.apply_theme(theme);
+        }
+    }
+
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let (pad_left, pad_right, pad_top, pad_bottom) = self.padding();
+        let inner = constraints.shrink(pad_left + pad_right, pad_top + pad_bottom);
+
+        // The tracks are sized from the natural sizes of the children
+        let mut sizes = self.children.iter_mut()
+            .map(|child| child.layout(inner.loosen()))
+            .collect::<Vec<_>>();
+        let columns = self.size_columns(&sizes, inner.max_width);
+        let rows = self.size_rows(&sizes, inner.max_height);
+        let cells = self.cell_rects(&columns, &rows);
+
+        // Lay out the children again in their cells if they don't fit or are stretched
+        for (i, (cell, area)) in self.cells.iter().zip(&cells).enumerate() {
+            let stretch_x = cell.align_x == Align::Stretch;
+            let stretch_y = cell.align_y == Align::Stretch;
+            if sizes[i].width <= area.w && sizes[i].height <= area.h && !stretch_x && !stretch_y {
+                continue;
+            }
+            let min_width = if stretch_x { area.w } else { 0.0 };
+            let min_height = if stretch_y { area.h } else { 0.0 };
+            sizes[i] = self.children[i].layout(Constraints::new(min_width, area.w, min_height, area.h));
+        }
+
+        let size = constraints.constrain(self.measure(&columns, &rows));
+        self.rects = self.arrange(&sizes, &cells);
+        self.size = Some(size);
+        self.laid_out = true;
+        size
+    }
+
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        layout_unless_laid_out(self, |grid| &mut grid.laid_out);
+
+        // The children drawn last are on top, so they get the mouse first
+        for (child, rect) in self.children.iter_mut().zip(&self.rects).rev() {
+            child.update(x + rect.x, y + rect.y, input);
+        }
+        // A background hides whatever is under it from the mouse
+        if let Some(size) = self.size && self.style.bg.a > 0.0 && input.hit_test(x, y, size.width, size.height) {
+            input.consume_mouse();
+        }
+    }
+
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let unlaid;
+        let (size, rects) = match self.size {
+            Some(size) => (size, &self.rects),
+            None => {
+                unlaid = self.unlaid();
+                (unlaid.0, &unlaid.1)
+            }
+        };
+
+        self.style.draw_background(painter, x, y, size.width, size.height, self.style.bg);
+
+        for (child, rect) in self.children.iter().zip(rects) {
+            child.render(x + rect.x, y + rect.y, painter);
+        }
+
+        self.style.draw_border(painter, x, y, size.width, size.height, self.style.fg);
+    }
+}
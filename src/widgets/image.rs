@@ -1,15 +1,21 @@
-//! This module defines the [`Image`] widget that displays an image on the screen.
+//! This module defines the [`Image`] widget that displays an image on the screen, and the
+//! [`ImageBuilder`] which makes one.
 use std::any::Any;
 
 use macroquad::prelude::*;
 
+use crate::input::InputState;
+use crate::layout::{Constraints, Size};
+use crate::painter::Painter;
 use crate::widgets::widget::Widget;
 
 /// The [`Image`] widget that displays an image on the screen.
 pub struct Image {
     height: f32,
     width: f32,
-    texture: Texture2D
+    texture: Texture2D,
+    preferred: Size,
+    id: Option<String>,
 }
 
 impl Image {
@@ -18,9 +24,65 @@ impl Image {
         Self {
             height,
             width,
-            texture
+            texture,
+            preferred: Size::new(width, height),
+            id: None,
         }
     }
+
+    /// Returns an [`ImageBuilder`] for an image of `texture`, as big as the texture.
+    pub fn builder(texture: Texture2D) -> ImageBuilder {
+        let (width, height) = (texture.width(), texture.height());
+        ImageBuilder {
+            image: Image::new(height, width, texture),
+        }
+    }
+
+    /// Sets the ID of the [`Image`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+}
+
+/// The [`ImageBuilder`] struct makes an [`Image`] one setting at a time, made with
+/// [`Image::builder`].
+///
+/// ```no_run
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// # async fn run() {
+/// let texture = load_texture("examples/flowquad.png").await.unwrap();
+/// let image = flowquad::widgets::image::Image::builder(texture).size(256.0, 128.0).build();
+/// assert_eq!((image.width(), image.height()), (256.0, 128.0));
+/// # }
+/// ```
+pub struct ImageBuilder {
+    image: Image,
+}
+
+impl ImageBuilder {
+    /// Sets the size of the image.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.image.width = width;
+        self.image.height = height;
+        self.image.preferred = Size::new(width, height);
+        self
+    }
+
+    /// Sets the ID of the image, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.image = self.image.with_id(id);
+        self
+    }
+
+    /// Makes the [`Image`].
+    pub fn build(self) -> Image {
+        self.image
+    }
 }
 
 impl Widget for Image {
@@ -28,6 +90,14 @@ impl Widget for Image {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
     fn width(&self) -> f32 {
         self.width
     }
@@ -40,14 +110,18 @@ impl Widget for Image {
         Color::new(0.0, 0.0, 0.0, 0.0)
     }
 
-    fn update(&mut self, _x: f32, _y: f32) {
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let size = constraints.constrain(self.preferred);
+        self.width = size.width;
+        self.height = size.height;
+        size
+    }
+
+    fn update(&mut self, _x: f32, _y: f32, _input: &mut InputState) {
         // Nothing :D
     }
 
-    fn render(&self, x: f32, y: f32) {
-        draw_texture_ex(&self.texture, x, y, WHITE, DrawTextureParams {
-            dest_size: Some(vec2(self.width, self.height)),
-            ..Default::default()
-        });
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        painter.draw_texture(&self.texture, x, y, self.width, self.height, WHITE);
     }
 }
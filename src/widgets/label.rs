@@ -1,30 +1,146 @@
-//! This module defines the [`Label`] widget that displays text on the screen.
+//! This module defines the [`Label`] widget that displays text on the screen, and the
+//! [`LabelBuilder`] which makes one.
 use std::any::Any;
 
 use macroquad::prelude::*;
 
+use crate::input::InputState;
+use crate::layout::{Constraints, Size};
+use crate::painter::{with_text_measure, Painter};
+use crate::theme::{Style, StyleOverride, Theme};
+
 use super::widget::Widget;
 
 /// The [`Label`] widget that displays text on the screen.
 pub struct Label {
     text: String,
-    bg: Color,
-    fg: Color,
-    font: Option<Font>,
-    size: f32,
+    style: Style,
+    overrides: StyleOverride,
+    laid_out: Option<Size>,
+    id: Option<String>,
 }
 
 impl Label {
-    /// Creates a new [`Label`] widget.
+    /// Creates a new [`Label`] widget with the specified colors, font and font size, which are
+    /// kept when it's restyled.
     pub fn new(text: String, bg: Color, fg: Color, font: Option<Font>, size: f32) -> Self {
+        Self::themed(text).with_style(StyleOverride {
+            bg: Some(bg),
+            fg: Some(fg),
+            font,
+            font_size: Some(size),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new [`Label`] widget styled by the global [`Theme`].
+    pub fn themed(text: String) -> Self {
         Self {
             text,
-            bg,
-            fg,
-            font,
-            size,
+            style: Theme::global().label,
+            overrides: StyleOverride::new(),
+            laid_out: None,
+            id: None,
         }
     }
+
+    /// Returns a [`LabelBuilder`] for a label without text, styled by the global [`Theme`].
+    pub fn builder() -> LabelBuilder {
+        LabelBuilder {
+            label: Label::themed(String::new()),
+        }
+    }
+
+    /// Overrides part of the style of the [`Label`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self.laid_out = None;
+        self
+    }
+
+    /// Sets the ID of the [`Label`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Returns the text displayed by the [`Label`] widget.
+    pub fn get_text(&self) -> String {
+        self.text.clone()
+    }
+
+    /// Replaces the text displayed by the [`Label`] widget.
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.laid_out = None;
+    }
+}
+
+/// The [`LabelBuilder`] struct makes a [`Label`] one setting at a time, made with
+/// [`Label::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let label = Label::builder()
+///     .text("Score: 0".to_string())
+///     .font_size(24.0)
+///     .id("score".to_string())
+///     .build();
+/// assert_eq!(label.get_text(), "Score: 0");
+/// assert_eq!(label.height(), 24.0);
+/// ```
+pub struct LabelBuilder {
+    label: Label,
+}
+
+impl LabelBuilder {
+    /// Sets the text of the label.
+    pub fn text(mut self, text: String) -> Self {
+        self.label.set_text(text);
+        self
+    }
+
+    /// Sets the ID of the label, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.label = self.label.with_id(id);
+        self
+    }
+
+    /// Overrides part of the style of the label, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.label = self.label.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the label.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Sets the foreground color of the label.
+    pub fn fg(self, fg: Color) -> Self {
+        self.style(StyleOverride::new().fg(fg))
+    }
+
+    /// Sets the font of the label.
+    pub fn font(self, font: Font) -> Self {
+        self.style(StyleOverride::new().font(font))
+    }
+
+    /// Sets the font size of the label.
+    pub fn font_size(self, font_size: f32) -> Self {
+        self.style(StyleOverride::new().font_size(font_size))
+    }
+
+    /// Makes the [`Label`].
+    pub fn build(self) -> Label {
+        self.label
+    }
 }
 
 impl Widget for Label {
@@ -32,42 +148,61 @@ impl Widget for Label {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
     fn width(&self) -> f32 {
-        let size = self.height() as u16;
-        let text_size = measure_text(&self.text, self.font.as_ref(), size, 1.0);
+        if let Some(laid_out) = self.laid_out {
+            return laid_out.width;
+        }
+        let size = self.style.font_size as u16;
+        let text_size = with_text_measure(|measure| measure.measure_text(&self.text, self.style.font.as_ref(), size));
         text_size.width
     }
 
     fn height(&self) -> f32 {
-        self.size
+        self.laid_out.map_or(self.style.font_size, |laid_out| laid_out.height)
     }
 
     fn bg(&self) -> Color {
-        self.bg
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.label);
+        self.laid_out = None;
     }
 
-    fn update(&mut self, _x: f32, _y: f32) {
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        self.laid_out = None;
+        let size = constraints.constrain(Size::new(self.width(), self.height()));
+        self.laid_out = Some(size);
+        size
+    }
+
+    fn update(&mut self, _x: f32, _y: f32, _input: &mut InputState) {
         // Nothing :D
     }
 
-    fn render(&self, x: f32, y: f32) {
-        let bg = self.bg;
-        let fg = self.fg;
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let bg = self.style.bg;
+        let fg = self.style.fg;
+
+        let size = self.style.font_size as u16;
 
-        let size = self.height() as u16;
-        
-        let text_size = measure_text(&self.text, self.font.as_ref(), size, 1.0);
-        draw_rectangle(x, y, self.width(), self.height(), bg);
-        draw_text_ex(&self.text,
+        let text_size = painter.measure_text(&self.text, self.style.font.as_ref(), size);
+        let width = self.laid_out.map_or(text_size.width, |laid_out| laid_out.width);
+        self.style.draw_background(painter, x, y, width, self.height(), bg);
+        painter.draw_text(&self.text,
             x,
             y + text_size.height,
-            TextParams {
-                font: self.font.as_ref(),
-                font_size: size,
-                font_scale: 1.0,
-                color: fg,
-                ..Default::default()
-            }
+            self.style.font.as_ref(), size, fg
         );
+        self.style.draw_border(painter, x, y, width, self.height(), fg);
     }
 }
@@ -1,8 +1,12 @@
 //! This module contains all UI Widgets for Flowquad.
 pub mod widget;
 pub mod container;
+pub mod grid;
+pub mod stack;
+pub mod scrollview;
 pub mod label;
 pub mod textinput;
+pub mod textarea;
 pub mod image;
 pub mod button;
 pub mod toggle;
@@ -0,0 +1,456 @@
+//! This module defines the [`ScrollView`] widget, which shows part of a bigger widget and lets the
+//! user scroll around it, and the [`ScrollViewBuilder`] which makes one.
+use std::any::Any;
+
+use macroquad::prelude::*;
+
+use crate::event::Event;
+use crate::input::InputState;
+use crate::layout::{layout_unless_laid_out, Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::{Style, StyleOverride, Theme};
+
+use super::widget::Widget;
+
+/// The thickness of the scrollbars.
+const SCROLLBAR_SIZE: f32 = 10.0;
+/// The shortest a scrollbar thumb gets, so it can still be grabbed.
+const MIN_THUMB_SIZE: f32 = 20.0;
+/// How far one step of the mouse wheel scrolls.
+const WHEEL_STEP: f32 = 40.0;
+
+/// Returns how far a mouse wheel movement scrolls. Only its direction is used, since the platforms
+/// report the wheel in different units, from single steps to pixels.
+fn wheel_step(wheel: f32) -> f32 {
+    if wheel != 0.0 { wheel.signum() * WHEEL_STEP } else { 0.0 }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// The [`ScrollView`] widget shows its child through a viewport of a fixed size, and lets the user
+/// scroll it with the mouse wheel, holding Shift to scroll sideways, or by dragging the
+/// scrollbars.
+///
+/// Anything outside the viewport is clipped, and the child only sees the mouse while it's over
+/// the visible part of the child, so hidden buttons can't be clicked.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut list = Container::new(Direction::Vertical, Align::Start, 0.0, BLACK, None, None);
+/// for i in 0..10 {
+///     list.add_child(Box::new(Button::new(100.0, 50.0, i.to_string(), BLACK, WHITE, None).with_id(i.to_string())));
+/// }
+/// let mut scroll = ScrollView::new(120.0, 100.0, Box::new(list), BLACK, WHITE);
+///
+/// // Scroll down two steps, then click the middle of the viewport
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(50.0, 60.0).scroll(0.0, -1.0).scroll(0.0, -1.0).scroll(0.0, 0.0)
+///     .click(MouseButton::Left);
+/// while !input.is_finished() {
+///     scroll.layout(Constraints::unbounded());
+///     scroll.update(0.0, 0.0, &mut input.poll());
+/// }
+///
+/// assert_eq!(scroll.scroll_offset(), (0.0, 80.0));
+/// assert_eq!(scroll.take_events(), vec![Event::Clicked { id: Some("2".to_string()) }]);
+/// ```
+pub struct ScrollView {
+    child: Box<dyn Widget>,
+    style: Style,
+    overrides: StyleOverride,
+    preferred: Size,
+    width: f32,
+    height: f32,
+    horizontal: bool,
+    vertical: bool,
+    offset: (f32, f32),
+    content: Size,
+    /// Which scrollbars are shown, horizontal then vertical, worked out by the last layout.
+    scrollbars: (bool, bool),
+    /// The scrollbar being dragged and where on its thumb it was grabbed.
+    dragging: Option<(Axis, f32)>,
+    hover: bool,
+    laid_out: bool,
+    id: Option<String>,
+}
+
+impl ScrollView {
+    /// Creates a new [`ScrollView`] widget with a viewport of the specified size over `child`,
+    /// `fg` is the color of the scrollbars. The colors are kept when it's restyled.
+    pub fn new(width: f32, height: f32, child: Box<dyn Widget>, bg: Color, fg: Color) -> Self {
+        Self::themed(width, height, child).with_style(StyleOverride::new().bg(bg).fg(fg))
+    }
+
+    /// Creates a new [`ScrollView`] widget styled by the global [`Theme`], with a viewport of the
+    /// specified size over `child`. Its scrollbars are drawn in the foreground color.
+    pub fn themed(width: f32, height: f32, child: Box<dyn Widget>) -> Self {
+        Self {
+            child,
+            style: Theme::global().scroll_view,
+            overrides: StyleOverride::new(),
+            preferred: Size::new(width, height),
+            width,
+            height,
+            horizontal: true,
+            vertical: true,
+            offset: (0.0, 0.0),
+            content: Size::default(),
+            scrollbars: (false, false),
+            dragging: None,
+            hover: false,
+            laid_out: false,
+            id: None,
+        }
+    }
+
+    /// Returns a [`ScrollViewBuilder`] for a 400 by 300 scroll view over `child` which scrolls
+    /// both ways, styled by the global [`Theme`].
+    pub fn builder(child: Box<dyn Widget>) -> ScrollViewBuilder {
+        ScrollViewBuilder {
+            scroll: ScrollView::themed(400.0, 300.0, child),
+        }
+    }
+
+    /// Overrides part of the style of the [`ScrollView`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self
+    }
+
+    /// Sets the ID of the [`ScrollView`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets which directions the [`ScrollView`] scrolls in, both by default. In a direction it
+    /// doesn't scroll in, the child is limited to the size of the viewport.
+    pub fn with_scroll(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.horizontal = horizontal;
+        self.vertical = vertical;
+        self
+    }
+
+    /// Returns the child being scrolled.
+    pub fn get_child(&self) -> &dyn Widget {
+        self.child.as_ref()
+    }
+
+    /// Returns the child being scrolled, mutably.
+    pub fn get_child_mut(&mut self) -> &mut dyn Widget {
+        self.child.as_mut()
+    }
+
+    /// Returns how far the child is scrolled to the left and up.
+    pub fn scroll_offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    /// Scrolls the child to the specified offset, clamped so the viewport stays over the child.
+    ///
+    /// The scrollbars cover part of the viewport, so a child a little narrower than the scroll
+    /// view still scrolls sideways once the vertical scrollbar is shown.
+    ///
+    /// ```
+    /// use macroquad::prelude::*;
+    /// use flowquad::prelude::*;
+    ///
+    /// let child = Button::new(195.0, 300.0, "Long".to_string(), BLACK, WHITE, None);
+    /// let mut scroll = ScrollView::new(200.0, 100.0, Box::new(child), BLACK, WHITE);
+    /// scroll.layout(Constraints::unbounded());
+    ///
+    /// scroll.scroll_to(1000.0, 1000.0);
+    /// assert_eq!(scroll.scroll_offset(), (5.0, 210.0));
+    /// ```
+    pub fn scroll_to(&mut self, x: f32, y: f32) {
+        self.offset = (x, y);
+        self.clamp_offset();
+    }
+
+    /// Takes every [`Event`] queued by the child and its descendants during the last update.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        (self as &mut dyn Widget).take_events()
+    }
+
+    /// Works out which scrollbars the content needs, horizontal then vertical. They're decided
+    /// together, since showing one leaves less room for the content along the other axis.
+    fn needed_scrollbars(&self) -> (bool, bool) {
+        let (mut horizontal, mut vertical) = (false, false);
+        // Scrollbars only ever get added, so the second pass settles it
+        for _ in 0..2 {
+            horizontal = self.horizontal && self.content.width > self.width - if vertical { SCROLLBAR_SIZE } else { 0.0 };
+            vertical = self.vertical && self.content.height > self.height - if horizontal { SCROLLBAR_SIZE } else { 0.0 };
+        }
+        (horizontal, vertical)
+    }
+
+    fn shows_scrollbar(&self, axis: Axis) -> bool {
+        match axis {
+            Axis::Horizontal => self.scrollbars.0,
+            Axis::Vertical => self.scrollbars.1,
+        }
+    }
+
+    /// Returns the size of the viewport the child is visible through, without the scrollbars.
+    fn viewport(&self) -> Size {
+        let (horizontal, vertical) = self.scrollbars;
+        Size::new(
+            self.width - if vertical { SCROLLBAR_SIZE } else { 0.0 },
+            self.height - if horizontal { SCROLLBAR_SIZE } else { 0.0 },
+        )
+    }
+
+    fn max_offset(&self) -> (f32, f32) {
+        let viewport = self.viewport();
+        (
+            (self.content.width - viewport.width).max(0.0),
+            (self.content.height - viewport.height).max(0.0),
+        )
+    }
+
+    fn clamp_offset(&mut self) {
+        let (max_x, max_y) = self.max_offset();
+        self.offset = (self.offset.0.clamp(0.0, max_x), self.offset.1.clamp(0.0, max_y));
+    }
+
+    /// Returns the track and the thumb of a scrollbar, relative to the widget.
+    fn scrollbar(&self, axis: Axis) -> (Rect, Rect) {
+        let viewport = self.viewport();
+        let (max_x, max_y) = self.max_offset();
+        match axis {
+            Axis::Horizontal => {
+                let track = Rect::new(0.0, viewport.height, viewport.width, SCROLLBAR_SIZE);
+                let length = (track.w * viewport.width / self.content.width).clamp(MIN_THUMB_SIZE.min(track.w), track.w);
+                let position = if max_x > 0.0 { (track.w - length) * self.offset.0 / max_x } else { 0.0 };
+                (track, Rect::new(position, track.y, length, track.h))
+            }
+            Axis::Vertical => {
+                let track = Rect::new(viewport.width, 0.0, SCROLLBAR_SIZE, viewport.height);
+                let length = (track.h * viewport.height / self.content.height).clamp(MIN_THUMB_SIZE.min(track.h), track.h);
+                let position = if max_y > 0.0 { (track.h - length) * self.offset.1 / max_y } else { 0.0 };
+                (track, Rect::new(track.x, position, track.w, length))
+            }
+        }
+    }
+
+    /// Scrolls so the thumb of the scrollbar is at `position` along its track.
+    fn drag_thumb(&mut self, axis: Axis, position: f32) {
+        let (track, thumb) = self.scrollbar(axis);
+        let (max_x, max_y) = self.max_offset();
+        match axis {
+            Axis::Horizontal if track.w > thumb.w => self.offset.0 = position / (track.w - thumb.w) * max_x,
+            Axis::Vertical if track.h > thumb.h => self.offset.1 = position / (track.h - thumb.h) * max_y,
+            _ => {}
+        }
+        self.clamp_offset();
+    }
+
+    fn handle_scrollbars(&mut self, mouse_x: f32, mouse_y: f32, input: &InputState) {
+        if !input.is_mouse_button_down(MouseButton::Left) {
+            self.dragging = None;
+        }
+        if let Some((axis, grab)) = self.dragging {
+            let position = if axis == Axis::Horizontal { mouse_x } else { mouse_y };
+            self.drag_thumb(axis, position - grab);
+        }
+
+        if !self.hover || !input.is_mouse_button_pressed(MouseButton::Left) {
+            return;
+        }
+        for axis in [Axis::Horizontal, Axis::Vertical] {
+            let (track, thumb) = self.scrollbar(axis);
+            if !self.shows_scrollbar(axis) || !track.contains(vec2(mouse_x, mouse_y)) {
+                continue;
+            }
+            let (position, thumb_start, thumb_length) = match axis {
+                Axis::Horizontal => (mouse_x, thumb.x, thumb.w),
+                Axis::Vertical => (mouse_y, thumb.y, thumb.h),
+            };
+            // Grabbing the track outside the thumb centers the thumb under the mouse first
+            let grab = if position >= thumb_start && position <= thumb_start + thumb_length {
+                position - thumb_start
+            } else {
+                self.drag_thumb(axis, position - thumb_length / 2.0);
+                thumb_length / 2.0
+            };
+            self.dragging = Some((axis, grab));
+        }
+    }
+}
+
+/// The [`ScrollViewBuilder`] struct makes a [`ScrollView`] one setting at a time, made with
+/// [`ScrollView::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let list = Container::builder()
+///     .child(Box::new(Button::builder().size(200.0, 500.0).build()))
+///     .build();
+/// let mut scroll = ScrollView::builder(Box::new(list))
+///     .size(200.0, 100.0)
+///     .scroll(false, true)
+///     .build();
+///
+/// assert_eq!(scroll.layout(Constraints::unbounded()), Size::new(200.0, 100.0));
+/// ```
+pub struct ScrollViewBuilder {
+    scroll: ScrollView,
+}
+
+impl ScrollViewBuilder {
+    /// Sets the size of the viewport.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.scroll.width = width;
+        self.scroll.height = height;
+        self.scroll.preferred = Size::new(width, height);
+        self
+    }
+
+    /// Sets which ways the scroll view scrolls, see [`ScrollView::with_scroll`].
+    pub fn scroll(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.scroll = self.scroll.with_scroll(horizontal, vertical);
+        self
+    }
+
+    /// Sets the ID of the scroll view, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.scroll = self.scroll.with_id(id);
+        self
+    }
+
+    /// Overrides part of the style of the scroll view, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.scroll = self.scroll.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the scroll view.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Sets the foreground color of the scroll view.
+    pub fn fg(self, fg: Color) -> Self {
+        self.style(StyleOverride::new().fg(fg))
+    }
+
+    /// Makes the [`ScrollView`].
+    pub fn build(self) -> ScrollView {
+        self.scroll
+    }
+}
+
+impl Widget for ScrollView {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        vec![self.child.as_ref()]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        vec![self.child.as_mut()]
+    }
+
+    fn width(&self) -> f32 {
+        self.width
+    }
+
+    fn height(&self) -> f32 {
+        self.height
+    }
+
+    fn bg(&self) -> Color {
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.scroll_view);
+        self.child.apply_theme(theme);
+    }
+
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let size = constraints.constrain(self.preferred);
+        self.width = size.width;
+        self.height = size.height;
+
+        let max_width = if self.horizontal { f32::INFINITY } else { size.width - if self.vertical { SCROLLBAR_SIZE } else { 0.0 } };
+        let max_height = if self.vertical { f32::INFINITY } else { size.height - if self.horizontal { SCROLLBAR_SIZE } else { 0.0 } };
+        self.content = self.child.layout(Constraints::loose(max_width.max(0.0), max_height.max(0.0)));
+        self.scrollbars = self.needed_scrollbars();
+        self.clamp_offset();
+        self.laid_out = true;
+        size
+    }
+
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        layout_unless_laid_out(self, |scroll| &mut scroll.laid_out);
+
+        self.hover = input.hit_test(x, y, self.width, self.height);
+        let (mouse_x, mouse_y) = input.mouse_position();
+        self.handle_scrollbars(mouse_x - x, mouse_y - y, input);
+
+        // The child only sees the mouse over the visible part of it, and not while a scrollbar
+        // is dragged
+        let viewport = self.viewport();
+        if self.dragging.is_some() {
+            input.push_mouse_clip(x, y, 0.0, 0.0);
+        } else {
+            input.push_mouse_clip(x, y, viewport.width, viewport.height);
+        }
+        self.child.update(x - self.offset.0, y - self.offset.1, input);
+        input.pop_mouse_clip();
+
+        // The child goes first, so the innermost scrolling widget takes the wheel
+        if self.hover {
+            let (wheel_x, wheel_y) = input.take_mouse_wheel();
+            let (wheel_x, wheel_y) = if input.is_shift_down() || !self.vertical { (wheel_y, wheel_x) } else { (wheel_x, wheel_y) };
+            self.offset.0 -= wheel_step(wheel_x);
+            self.offset.1 -= wheel_step(wheel_y);
+            self.clamp_offset();
+        }
+        if self.hover || self.dragging.is_some() {
+            input.consume_mouse();
+        }
+    }
+
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        self.style.draw_background(painter, x, y, self.width, self.height, self.style.bg);
+
+        let viewport = self.viewport();
+        painter.push_clip(x, y, viewport.width, viewport.height);
+        self.child.render(x - self.offset.0, y - self.offset.1, painter);
+        painter.pop_clip();
+
+        for axis in [Axis::Horizontal, Axis::Vertical] {
+            if !self.shows_scrollbar(axis) {
+                continue;
+            }
+            let (track, thumb) = self.scrollbar(axis);
+            let thumb_color = if self.dragging.is_some_and(|(dragged, _)| dragged == axis) { self.style.fg } else { Color { a: 0.6, ..self.style.fg } };
+            painter.draw_rectangle(x + track.x, y + track.y, track.w, track.h, Color { a: 0.2, ..self.style.fg });
+            painter.draw_rectangle(x + thumb.x, y + thumb.y, thumb.w, thumb.h, thumb_color);
+        }
+        self.style.draw_border(painter, x, y, self.width, self.height, self.style.fg);
+    }
+}
@@ -0,0 +1,463 @@
+//! This module defines the [`Stack`] widget, which draws other widgets on top of each other.
+//!
+//! This module contains the following:
+//! 1. The [`Stack`] struct, which places its children at anchored positions over each other, it
+//!    itself implements the [`Widget`] trait.
+//! 2. The [`Anchor`] enum, which defines the point of the stack a child is pinned to.
+//! 3. The [`StackPosition`] struct, which defines the anchor, offset and z-order of a child.
+//! 4. The [`StackBuilder`] struct, which makes a [`Stack`] one setting at a time.
+use std::any::Any;
+use std::cell::Cell;
+
+use macroquad::prelude::*;
+
+use crate::input::InputState;
+use crate::layout::{intrinsic_sizes, layout_unless_laid_out, Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::{Style, StyleOverride, Theme};
+
+use super::widget::Widget;
+
+/// The [`Anchor`] enum defines the point of a [`Stack`] a child is pinned to. The same point of
+/// the child is put on it, so a child anchored to the bottom right sits in the bottom right
+/// corner.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Returns where the anchor is as fractions of the width and height.
+    fn fractions(&self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// The [`StackPosition`] struct defines where a child of a [`Stack`] goes: the [`Anchor`] it's
+/// pinned to, an offset from there, and its z-order. Children with a higher `z` are drawn over
+/// the others, children with the same `z` are drawn in the order they were added.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StackPosition {
+    pub anchor: Anchor,
+    pub offset: (f32, f32),
+    pub z: i32,
+}
+
+impl StackPosition {
+    /// Creates a new [`StackPosition`] pinned to the specified [`Anchor`].
+    pub fn new(anchor: Anchor) -> Self {
+        Self {
+            anchor,
+            offset: (0.0, 0.0),
+            z: 0,
+        }
+    }
+
+    /// Moves the child by the specified offset from its anchor, positive values move it right and
+    /// down.
+    pub fn offset(mut self, x: f32, y: f32) -> Self {
+        self.offset = (x, y);
+        self
+    }
+
+    /// Sets the z-order of the child.
+    pub fn z(mut self, z: i32) -> Self {
+        self.z = z;
+        self
+    }
+}
+
+/// The [`Stack`] struct draws other widgets on top of each other, each pinned to an [`Anchor`] of
+/// the stack's rect, it itself implements the [`Widget`] trait.
+///
+/// The stack is as big as its biggest child unless it's given a size, children may stick out of
+/// it. Children are updated from the top one down.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut stack = Stack::new(BLANK);
+/// stack.add_child(Box::new(Button::new(120.0, 40.0, "Inbox".to_string(), BLACK, WHITE, None)), StackPosition::new(Anchor::TopLeft));
+/// stack.add_child(Box::new(Button::new(20.0, 20.0, "3".to_string(), RED, WHITE, None)), StackPosition::new(Anchor::TopRight).offset(10.0, -10.0).z(1));
+///
+/// assert_eq!(stack.layout(Constraints::unbounded()), Size::new(120.0, 40.0));
+/// assert_eq!(stack.child_rect(1), Some(Rect::new(110.0, -10.0, 20.0, 20.0)));
+/// ```
+pub struct Stack {
+    children: Vec<Box<dyn Widget>>,
+    positions: Vec<StackPosition>,
+    style: Style,
+    overrides: StyleOverride,
+    constraints: Constraints,
+    id: Option<String>,
+    /// The size from the last layout, if the stack was laid out since its children changed.
+    size: Option<Size>,
+    /// The size the stack takes up before it's laid out, measured once since its children
+    /// changed, as it's measured from the whole subtree.
+    intrinsic: Cell<Option<Size>>,
+    /// Where every child goes, relative to the stack, from the last layout.
+    rects: Vec<Rect>,
+    /// Whether the stack was laid out since the last update, and hasn't changed since.
+    laid_out: bool,
+}
+
+impl Stack {
+    /// Creates a new [`Stack`] widget with the specified background, which is kept when it's
+    /// restyled.
+    pub fn new(bg: Color) -> Self {
+        Self::themed().with_style(StyleOverride::new().bg(bg))
+    }
+
+    /// Creates a new [`Stack`] widget styled by the global [`Theme`]. Its border is drawn in the
+    /// foreground color, and its padding is ignored since children are pinned to its edges.
+    pub fn themed() -> Self {
+        Self {
+            children: Vec::new(),
+            positions: Vec::new(),
+            style: Theme::global().stack,
+            overrides: StyleOverride::new(),
+            constraints: Constraints::unbounded(),
+            id: None,
+            size: None,
+            intrinsic: Cell::new(None),
+            rects: Vec::new(),
+            laid_out: false,
+        }
+    }
+
+    /// Returns a [`StackBuilder`] for an empty stack as big as its biggest child, styled by the
+    /// global [`Theme`].
+    pub fn builder() -> StackBuilder {
+        StackBuilder {
+            stack: Stack::themed(),
+        }
+    }
+
+    /// Overrides part of the style of the [`Stack`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self.invalidate();
+        self
+    }
+
+    /// Sets the ID of the [`Stack`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Makes the stack exactly `width` wide and `height` tall, whatever its children need.
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.constraints = Constraints::tight(width, height);
+        self.invalidate();
+        self
+    }
+
+    /// Adds a child [`Widget`] to the stack at the specified [`StackPosition`].
+    pub fn add_child(&mut self, child: Box<dyn Widget>, position: StackPosition) {
+        self.invalidate();
+        self.children.push(child);
+        self.positions.push(position);
+    }
+
+    /// Gets a child [`Widget`] from the stack, in the order they were added.
+    pub fn get_child(&self, index: usize) -> Option<&dyn Widget> {
+        self.children.get(index).map(|child| child.as_ref())
+    }
+
+    /// Gets a child [`Widget`] from the stack and downcasts it to the specified type.
+    pub fn get_child_as<T: 'static>(&self, index: usize) -> Option<&T> {
+        self.children.get(index)?
+            .as_any()
+            .downcast_ref::<T>()
+    }
+
+    /// Gets a mutable child [`Widget`] from the stack and downcasts it to the specified type.
+    pub fn get_child_as_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
+        self.children.get_mut(index)?
+            .as_any_mut()
+            .downcast_mut::<T>()
+    }
+
+    /// Removes the child [`Widget`] at the specified index and returns it.
+    pub fn remove_child(&mut self, index: usize) -> Option<Box<dyn Widget>> {
+        if index < self.children.len() {
+            self.invalidate();
+            self.positions.remove(index);
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Moves the child at the specified index to another [`StackPosition`].
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set_position(&mut self, index: usize, position: StackPosition) {
+        self.invalidate();
+        self.positions[index] = position;
+    }
+
+    /// Removes every child from the stack.
+    pub fn clear(&mut self) {
+        self.invalidate();
+        self.children.clear();
+        self.positions.clear();
+    }
+
+    /// Returns the number of children in the stack.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Returns if the stack has no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Returns where the child at the specified index goes, relative to the top left of the
+    /// stack, as worked out by the last [`Widget::layout`].
+    pub fn child_rect(&self, index: usize) -> Option<Rect> {
+        self.size.and(self.rects.get(index).copied())
+    }
+
+    /// Finds a descendant [`Widget`] by its ID path and downcasts it to the specified type. See
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn find<T: 'static>(&self, path: &str) -> Option<&T> {
+        (self as &dyn Widget).find::<T>(path)
+    }
+
+    /// Finds a descendant [`Widget`] by its ID path and mutably downcasts it to the specified
+    /// type.
+    pub fn find_mut<T: 'static>(&mut self, path: &str) -> Option<&mut T> {
+        (self as &mut dyn Widget).find_mut::<T>(path)
+    }
+
+    /// Returns the indices of the children from the bottom one to the top one.
+    fn z_order(&self) -> Vec<usize> {
+        let mut order = (0..self.children.len()).collect::<Vec<_>>();
+        order.sort_by_key(|i| self.positions[*i].z);
+        order
+    }
+
+    /// Pins children of the specified sizes to their anchors in a stack of the specified size.
+    fn arrange(&self, sizes: &[Size], size: Size) -> Vec<Rect> {
+        self.positions.iter().zip(sizes)
+            .map(|(position, child)| {
+                let (fraction_x, fraction_y) = position.anchor.fractions();
+                Rect::new(
+                    (size.width - child.width) * fraction_x + position.offset.0,
+                    (size.height - child.height) * fraction_y + position.offset.1,
+                    child.width,
+                    child.height,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the size of a stack holding children of the specified sizes.
+    fn measure(&self, sizes: &[Size]) -> Size {
+        Size::new(
+            sizes.iter().map(|s| s.width).fold(0.0, f32::max),
+            sizes.iter().map(|s| s.height).fold(0.0, f32::max),
+        )
+    }
+
+    /// Forgets the size from the last layout and the intrinsic size, after something which
+    /// changes them.
+    fn invalidate(&mut self) {
+        self.size = None;
+        self.intrinsic.set(None);
+        self.laid_out = false;
+    }
+
+    /// Returns the size the stack takes up without laying it out, which is only measured once
+    /// until its children change.
+    fn intrinsic_size(&self) -> Size {
+        if let Some(size) = self.intrinsic.get() {
+            return size;
+        }
+        let size = self.unlaid().0;
+        self.intrinsic.set(Some(size));
+        size
+    }
+
+    /// Returns the size and child rects of the stack without laying it out, from the current
+    /// sizes of its children.
+    fn unlaid(&self) -> (Size, Vec<Rect>) {
+        let sizes = intrinsic_sizes(&self.children);
+        let size = self.constraints.constrain(self.measure(&sizes));
+        (size, self.arrange(&sizes, size))
+    }
+}
+
+/// The [`StackBuilder`] struct makes a [`Stack`] one setting at a time, made with
+/// [`Stack::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut stack = Stack::builder()
+///     .size(300.0, 200.0)
+///     .child(Box::new(Button::builder().size(100.0, 40.0).build()), StackPosition::new(Anchor::Center))
+///     .build();
+///
+/// stack.layout(Constraints::unbounded());
+/// assert_eq!(stack.child_rect(0), Some(Rect::new(100.0, 80.0, 100.0, 40.0)));
+/// ```
+pub struct StackBuilder {
+    stack: Stack,
+}
+
+impl StackBuilder {
+    /// Makes the stack exactly `width` wide and `height` tall, whatever its children need.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.stack = self.stack.with_size(width, height);
+        self
+    }
+
+    /// Overrides part of the style of the stack, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.stack = self.stack.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the stack.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Draws a border of the specified width and color around the stack.
+    pub fn border(self, width: f32, color: Color) -> Self {
+        self.style(StyleOverride::new().border_width(width).fg(color))
+    }
+
+    /// Sets the ID of the stack, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.stack = self.stack.with_id(id);
+        self
+    }
+
+    /// Adds a child [`Widget`] to the stack at the specified [`StackPosition`].
+    pub fn child(mut self, child: Box<dyn Widget>, position: StackPosition) -> Self {
+        self.stack.add_child(child, position);
+        self
+    }
+
+    /// Makes the [`Stack`].
+    pub fn build(self) -> Stack {
+        self.stack
+    }
+}
+
+impl Widget for Stack {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        self.children.iter().map(|child| child.as_ref()).collect()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.children.iter_mut().map(|child| child.as_mut()).collect()
+    }
+
+    fn width(&self) -> f32 {
+        self.size.unwrap_or_else(|| self.intrinsic_size()).width
+    }
+
+    fn height(&self) -> f32 {
+        self.size.unwrap_or_else(|| self.intrinsic_size()).height
+    }
+
+    fn bg(&self) -> Color {
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.stack);
+        self.invalidate();
+        for child in &mut self.children {
+            child.apply_theme(theme);
+        }
+    }
+
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let constraints = self.constraints.enforce(constraints);
+        let sizes = self.children.iter_mut()
+            .map(|child| child.layout(constraints.loosen()))
+            .collect::<Vec<_>>();
+        let size = constraints.constrain(self.measure(&sizes));
+
+        self.rects = self.arrange(&sizes, size);
+        self.size = Some(size);
+        self.laid_out = true;
+        size
+    }
+
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        layout_unless_laid_out(self, |stack| &mut stack.laid_out);
+
+        for i in self.z_order().into_iter().rev() {
+            let rect = self.rects[i];
+            self.children[i].update(x + rect.x, y + rect.y, input);
+        }
+        // A background hides whatever is under it from the mouse
+        if let Some(size) = self.size && self.style.bg.a > 0.0 && input.hit_test(x, y, size.width, size.height) {
+            input.consume_mouse();
+        }
+    }
+
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let unlaid;
+        let (size, rects) = match self.size {
+            Some(size) => (size, &self.rects),
+            None => {
+                unlaid = self.unlaid();
+                (unlaid.0, &unlaid.1)
+            }
+        };
+
+        self.style.draw_background(painter, x, y, size.width, size.height, self.style.bg);
+
+        for i in self.z_order() {
+            self.children[i].render(x + rects[i].x, y + rects[i].y, painter);
+        }
+
+        self.style.draw_border(painter, x, y, size.width, size.height, self.style.fg);
+    }
+}
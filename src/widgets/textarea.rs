@@ -0,0 +1,762 @@
+//! This module defines the [`TextArea`] widget that allows the user to enter multiple lines of
+//! text, and the [`TextAreaBuilder`] which makes one.
+use std::any::Any;
+use std::cell::{Cell, Ref, RefCell};
+
+use macroquad::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::editor::TextEditor;
+use crate::event::Event;
+use crate::input::{InputState, DOUBLE_CLICK_TIME};
+use crate::layout::{Constraints, Size};
+use crate::painter::{with_text_measure, Painter, TextMeasure};
+use crate::theme::{Style, StyleOverride, Theme};
+
+use super::textinput::{handle_editing_keys, is_key_repeated, EditOptions, TextCallback};
+use super::widget::{Action, Widget};
+
+/// The number of lines scrolled by one step of the mouse wheel.
+const WHEEL_LINES: usize = 3;
+
+/// A line of text as it's shown on the screen, after wrapping.
+struct VisualLine {
+    /// The index of the line in the text, counting only newlines.
+    line: usize,
+    /// The byte offset and x position of every grapheme boundary in the line, starting with its
+    /// start and ending with its end.
+    carets: Vec<(usize, f32)>,
+    /// The index of the last caret the cursor can be put at, before the spaces hanging over the
+    /// edge where the line was wrapped.
+    last: usize,
+}
+
+impl VisualLine {
+    fn start(&self) -> usize {
+        self.carets[0].0
+    }
+
+    fn end(&self) -> usize {
+        self.carets[self.carets.len() - 1].0
+    }
+
+    /// Returns the byte offset the End key moves the cursor to.
+    fn caret_end(&self) -> usize {
+        self.carets[self.last].0
+    }
+
+    /// Returns the x position of the specified byte offset, clamped to the line.
+    fn x_of(&self, offset: usize) -> f32 {
+        self.carets.iter().find(|c| c.0 >= offset).unwrap_or(&self.carets[self.carets.len() - 1]).1
+    }
+
+    /// Returns the byte offset of the grapheme boundary closest to `x`, up to the spaces hanging
+    /// over the edge.
+    fn offset_at(&self, x: f32) -> usize {
+        self.carets[..=self.last].iter()
+            .min_by(|a, b| (a.1 - x).abs().total_cmp(&(b.1 - x).abs()))
+            .map_or(self.start(), |c| c.0)
+    }
+}
+
+/// The text of a [`TextArea`] wrapped into [`VisualLine`]s, with the text and width it was wrapped
+/// for.
+struct Wrapping {
+    text: String,
+    width: f32,
+    /// The width of the line number gutter.
+    gutter: f32,
+    lines: Vec<VisualLine>,
+}
+
+/// Splits the text into lines on newlines, and wraps every line between words so it fits in
+/// `max_width`. Words which don't fit on a line of their own are broken between graphemes.
+fn wrap_text(measure: &dyn TextMeasure, text: &str, max_width: f32, font: Option<&Font>, size: u16) -> Vec<VisualLine> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+
+    for (line, hard_line) in text.split('\n').enumerate() {
+        let mut carets = vec![(line_start, 0.0)];
+        let mut last = 0;
+        let mut x = 0.0;
+
+        for (i, segment) in hard_line.split_word_bound_indices() {
+            let graphemes = segment.grapheme_indices(true)
+                .map(|(j, g)| (line_start + i + j, g.len(), measure.measure_text(g, font, size).width))
+                .collect::<Vec<_>>();
+            let segment_width = graphemes.iter().map(|g| g.2).sum::<f32>();
+            // Spaces may hang over the edge, so lines never start with the space they wrapped at
+            let breakable = !segment.chars().all(char::is_whitespace);
+
+            if breakable && x > 0.0 && x + segment_width > max_width {
+                lines.push(VisualLine { line, carets, last });
+                carets = vec![(line_start + i, 0.0)];
+                last = 0;
+                x = 0.0;
+            }
+            for (start, len, width) in graphemes {
+                if breakable && x > 0.0 && x + width > max_width {
+                    lines.push(VisualLine { line, carets, last });
+                    carets = vec![(start, 0.0)];
+                    last = 0;
+                    x = 0.0;
+                }
+                x += width;
+                carets.push((start + len, x));
+                if breakable {
+                    last = carets.len() - 1;
+                }
+            }
+        }
+
+        let last = carets.len() - 1;
+        lines.push(VisualLine { line, carets, last });
+        line_start += hard_line.len() + 1;
+    }
+
+    lines
+}
+
+/// The [`TextArea`] widget that allows the user to enter multiple lines of text.
+///
+/// Lines are wrapped between words to fit the width of the widget, and the text scrolls
+/// vertically with the mouse wheel or to follow the cursor. Besides the editing keys of a
+/// [`TextInput`](super::textinput::TextInput), Enter inserts a newline, Up and Down move the cursor
+/// by one line and Page Up and Page Down by a whole page. The text is wrapped with the global
+/// [`TextMeasure`] whenever it or the width changed, so the cursor keys always move through the
+/// current lines. End moves the cursor to the end of its visual line, before the spaces the line
+/// was wrapped at.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// set_text_measure(RecordingPainter::new());
+///
+/// let mut textarea = TextArea::new(200.0, 100.0, BLACK, WHITE, None, 16.0);
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(10.0, 10.0).click(MouseButton::Left)
+///     .type_text("first").tap_key(KeyCode::Enter).type_text("second")
+///     .tap_key(KeyCode::Up).tap_key(KeyCode::End).type_text("!");
+///
+/// while !input.is_finished() {
+///     textarea.update(0.0, 0.0, &mut input.poll());
+///     textarea.render(0.0, 0.0, &mut RecordingPainter::new());
+/// }
+/// assert_eq!(textarea.get_text(), "first!\nsecond");
+/// ```
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// set_text_measure(RecordingPainter::new());
+///
+/// // Fits 14 characters on a line, so the text wraps before "dddd"
+/// let mut textarea = TextArea::new(120.0, 100.0, BLACK, WHITE, None, 16.0);
+/// textarea.set_text("aaaa bbbb cccc dddd".to_string());
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(10.0, 10.0).click(MouseButton::Left)
+///     .tap_key(KeyCode::Home).tap_key(KeyCode::End).type_text("!");
+/// while !input.is_finished() {
+///     textarea.update(0.0, 0.0, &mut input.poll());
+/// }
+/// assert_eq!(textarea.get_text(), "aaaa bbbb cccc! dddd");
+///
+/// // A word too long for a line is broken inside, and End stays at the end of the first part
+/// textarea.set_text("abcdefghijklmnopqrst".to_string());
+/// input.tap_key(KeyCode::Up).tap_key(KeyCode::End).tap_key(KeyCode::Home).type_text("!");
+/// while !input.is_finished() {
+///     textarea.update(0.0, 0.0, &mut input.poll());
+/// }
+/// assert_eq!(textarea.get_text(), "!abcdefghijklmnopqrst");
+/// ```
+pub struct TextArea {
+    editor: TextEditor,
+    style: Style,
+    overrides: StyleOverride,
+    /// The color of the focus ring.
+    ring: Color,
+    hover: bool,
+    just_clicked: bool,
+    selected: bool,
+    position: (f32, f32),
+    width: f32,
+    height: f32,
+    line_numbers: bool,
+    options: EditOptions,
+    cooldown: u32,
+    dragging: bool,
+    last_click: f64,
+    first_line: Cell<usize>,
+    follow_cursor: Cell<bool>,
+    wrapping: RefCell<Option<Wrapping>>,
+    /// The cursor offset which is shown at the end of a line broken inside a word rather than at
+    /// the start of the next one, set when the cursor was put at the end of that line.
+    cursor_at_line_end: Option<usize>,
+    preferred: Size,
+    id: Option<String>,
+    events: Vec<Event>,
+    on_change: Option<TextCallback>,
+}
+
+impl TextArea {
+    /// Creates a new [`TextArea`] widget with the specified colors, font and font size, which are
+    /// kept when it's restyled.
+    pub fn new(width: f32, height: f32, bg: Color, fg: Color, font: Option<Font>, size: f32) -> Self {
+        Self::themed(width, height).with_style(StyleOverride {
+            bg: Some(bg),
+            fg: Some(fg),
+            font,
+            font_size: Some(size),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new [`TextArea`] widget styled by the global [`Theme`].
+    pub fn themed(width: f32, height: f32) -> Self {
+        let theme = Theme::global();
+        Self {
+            editor: TextEditor::new(),
+            style: theme.text_area,
+            overrides: StyleOverride::new(),
+            ring: theme.palette.accent,
+            hover: false,
+            just_clicked: false,
+            selected: false,
+            position: (0.0, 0.0),
+            width,
+            height,
+            line_numbers: false,
+            options: EditOptions { multiline: true, ..EditOptions::default() },
+            cooldown: 0,
+            dragging: false,
+            last_click: f64::NEG_INFINITY,
+            first_line: Cell::new(0),
+            follow_cursor: Cell::new(false),
+            wrapping: RefCell::new(None),
+            cursor_at_line_end: None,
+            preferred: Size::new(width, height),
+            id: None,
+            events: Vec::new(),
+            on_change: None,
+        }
+    }
+
+    /// Returns a [`TextAreaBuilder`] for an empty 400 by 200 text area, styled by the global
+    /// [`Theme`].
+    pub fn builder() -> TextAreaBuilder {
+        TextAreaBuilder {
+            textarea: TextArea::themed(400.0, 200.0),
+        }
+    }
+
+    /// Overrides part of the style of the [`TextArea`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        *self.wrapping.get_mut() = None;
+        self
+    }
+
+    /// Sets the ID of the [`TextArea`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Shows the number of every line in a gutter on the left of the [`TextArea`].
+    pub fn with_line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        *self.wrapping.get_mut() = None;
+        self
+    }
+
+    /// Sets a callback which is called with the new text every time the user edits it.
+    pub fn on_change(mut self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.on_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the text entered in the [`TextArea`] widget.
+    pub fn get_text(&self) -> String {
+        self.editor.text().to_string()
+    }
+
+    /// Replaces the text in the [`TextArea`] widget and moves the cursor to its end.
+    pub fn set_text(&mut self, text: String) {
+        self.editor.set_text(text);
+        self.follow_cursor.set(true);
+    }
+
+    /// Clears the text in the [`TextArea`] widget.
+    pub fn clear(&mut self) {
+        self.set_text(String::new());
+    }
+
+    /// Returns the selected text in the [`TextArea`] widget, which is empty if nothing is
+    /// selected.
+    pub fn get_selected_text(&self) -> String {
+        self.editor.selected_text().to_string()
+    }
+
+    /// Reverts the last edit, returns if there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let changed = self.editor.undo();
+        if changed {
+            self.follow_cursor.set(true);
+        }
+        changed
+    }
+
+    /// Re-applies the last undone edit, returns if there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let changed = self.editor.redo();
+        if changed {
+            self.follow_cursor.set(true);
+        }
+        changed
+    }
+
+    /// Forgets every edit, so they can't be undone or redone anymore.
+    pub fn clear_history(&mut self) {
+        self.editor.clear_history();
+    }
+
+    fn line_height(&self) -> f32 {
+        self.style.font_size * 1.25
+    }
+
+    /// Returns the number of lines which fit in the [`TextArea`] at once.
+    fn visible_lines(&self) -> usize {
+        let (_, _, pad_top, pad_bottom) = self.style.padding;
+        (((self.height - pad_top - pad_bottom) / self.line_height()) as usize).max(1)
+    }
+
+    /// Returns the text wrapped to the width of the [`TextArea`], which is only wrapped again once
+    /// the text or the width changed.
+    fn wrapping(&self) -> Ref<'_, Wrapping> {
+        let text = self.editor.text();
+        let stale = self.wrapping.borrow().as_ref()
+            .is_none_or(|wrapping| wrapping.text != text || wrapping.width != self.width);
+        if stale {
+            let (pad_left, pad_right, _, _) = self.style.padding;
+            let size = self.style.font_size as u16;
+            let font = self.style.font.as_ref();
+            let wrapping = with_text_measure(|measure| {
+                let gutter = if self.line_numbers {
+                    let digits = (text.matches('\n').count() + 1).to_string().len();
+                    measure.measure_text(&"0".repeat(digits), font, size).width + 8.0
+                } else {
+                    0.0
+                };
+                let lines = wrap_text(measure, text, self.width - pad_left - pad_right - gutter, font, size);
+                Wrapping { text: text.to_string(), width: self.width, gutter, lines }
+            });
+            *self.wrapping.borrow_mut() = Some(wrapping);
+        }
+        Ref::map(self.wrapping.borrow(), |wrapping| wrapping.as_ref().expect("the text was just wrapped"))
+    }
+
+    /// Returns the index of the visual line the cursor is on.
+    fn cursor_line(&self, lines: &[VisualLine]) -> usize {
+        let cursor = self.editor.cursor();
+        let index = lines.iter().rposition(|l| l.start() <= cursor).unwrap_or(0);
+        // A line broken inside a word ends where the next one starts
+        if index > 0 && self.cursor_at_line_end == Some(cursor) && lines[index - 1].caret_end() == cursor {
+            index - 1
+        } else {
+            index
+        }
+    }
+
+    /// Moves the cursor to the specified byte offset, and whether it's at the end of the visual
+    /// line it was found on.
+    fn place_cursor(&mut self, (offset, at_line_end): (usize, bool), extend: bool) {
+        self.editor.set_cursor(offset, extend);
+        self.cursor_at_line_end = at_line_end.then_some(offset);
+    }
+
+    /// Returns the byte offset closest to the specified position, relative to the top left of the
+    /// widget, and whether it's at the end of its visual line.
+    fn offset_at(&self, x: f32, y: f32) -> (usize, bool) {
+        let (pad_left, _, pad_top, _) = self.style.padding;
+        let wrapping = self.wrapping();
+        let lines = &wrapping.lines;
+        let index = ((y - pad_top) / self.line_height()).floor().max(0.0) as usize + self.first_line.get();
+        lines.get(index.min(lines.len().saturating_sub(1))).map_or((self.editor.text().len(), false), |line| {
+            let offset = line.offset_at(x - pad_left - wrapping.gutter);
+            (offset, offset == line.caret_end())
+        })
+    }
+
+    /// Moves the cursor by the specified number of visual lines, keeping its x position, returns
+    /// if anything changed.
+    fn move_vertically(&mut self, delta: isize, extend: bool) -> bool {
+        let target = {
+            let wrapping = self.wrapping();
+            let lines = &wrapping.lines;
+            if lines.is_empty() {
+                return false;
+            }
+            let current = self.cursor_line(lines);
+            let target = current.saturating_add_signed(delta).min(lines.len() - 1);
+            if target == current {
+                // Moving past the first or last line goes to the start or end of the text
+                (if delta < 0 { 0 } else { self.editor.text().len() }, false)
+            } else {
+                let offset = lines[target].offset_at(lines[current].x_of(self.editor.cursor()));
+                (offset, offset == lines[target].caret_end())
+            }
+        };
+        let previous = (self.editor.cursor(), self.editor.selection());
+        self.place_cursor(target, extend);
+        (self.editor.cursor(), self.editor.selection()) != previous
+    }
+
+    fn handle_keys(&mut self, input: &mut InputState) {
+        let shift = input.is_shift_down();
+        let page = self.visible_lines() as isize;
+        let repeat = |key| is_key_repeated(input, self.cooldown, key);
+        let (up, down, page_up, page_down, enter) = (
+            repeat(KeyCode::Up),
+            repeat(KeyCode::Down),
+            repeat(KeyCode::PageUp),
+            repeat(KeyCode::PageDown),
+            repeat(KeyCode::Enter) || repeat(KeyCode::KpEnter),
+        );
+
+        let mut acted = handle_editing_keys(&mut self.editor, input, self.cooldown, &self.options);
+        acted |= (up && self.move_vertically(-1, shift)) ||
+            (down && self.move_vertically(1, shift)) ||
+            (page_up && self.move_vertically(-page, shift)) ||
+            (page_down && self.move_vertically(page, shift));
+        if enter {
+            self.editor.insert_char('\n');
+            acted = true;
+        }
+        if acted {
+            self.cooldown = 2;
+        } else if self.cooldown > 0 {
+            self.cooldown -= 1;
+        }
+
+        let (home, end) = {
+            let wrapping = self.wrapping();
+            let lines = &wrapping.lines;
+            match lines.get(self.cursor_line(lines)) {
+                Some(line) if !input.is_ctrl_down() => ((line.start(), false), (line.caret_end(), true)),
+                _ => ((0, false), (self.editor.text().len(), false)),
+            }
+        };
+        if input.is_key_pressed(KeyCode::Home) {
+            self.place_cursor(home, shift);
+        }
+        if input.is_key_pressed(KeyCode::End) {
+            self.place_cursor(end, shift);
+        }
+    }
+}
+
+/// The [`TextAreaBuilder`] struct makes a [`TextArea`] one setting at a time, made with
+/// [`TextArea::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let notes = TextArea::builder()
+///     .size(300.0, 120.0)
+///     .text("First line\nSecond line".to_string())
+///     .line_numbers(true)
+///     .build();
+/// assert_eq!(notes.get_text(), "First line\nSecond line");
+/// ```
+pub struct TextAreaBuilder {
+    textarea: TextArea,
+}
+
+impl TextAreaBuilder {
+    /// Sets the size of the text area.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.textarea.width = width;
+        self.textarea.height = height;
+        self.textarea.preferred = Size::new(width, height);
+        self
+    }
+
+    /// Sets the text the text area starts with.
+    pub fn text(mut self, text: String) -> Self {
+        self.textarea.set_text(text);
+        self
+    }
+
+    /// Shows the number of every line in a gutter on the left of the text area.
+    pub fn line_numbers(mut self, line_numbers: bool) -> Self {
+        self.textarea = self.textarea.with_line_numbers(line_numbers);
+        self
+    }
+
+    /// Sets the ID of the text area, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.textarea = self.textarea.with_id(id);
+        self
+    }
+
+    /// Overrides part of the style of the text area, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.textarea = self.textarea.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the text area.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Sets the foreground color of the text area.
+    pub fn fg(self, fg: Color) -> Self {
+        self.style(StyleOverride::new().fg(fg))
+    }
+
+    /// Sets the font of the text area.
+    pub fn font(self, font: Font) -> Self {
+        self.style(StyleOverride::new().font(font))
+    }
+
+    /// Sets the font size of the text area.
+    pub fn font_size(self, font_size: f32) -> Self {
+        self.style(StyleOverride::new().font_size(font_size))
+    }
+
+    /// Sets a callback which is called with the new text every time the user edits it.
+    pub fn on_change(mut self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.textarea = self.textarea.on_change(callback);
+        self
+    }
+
+    /// Makes the [`TextArea`].
+    pub fn build(self) -> TextArea {
+        self.textarea
+    }
+}
+
+impl Widget for TextArea {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_focused(&self) -> bool {
+        self.selected
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.selected = focused;
+    }
+
+    fn edits_text(&self) -> bool {
+        true
+    }
+
+    fn screen_rect(&self) -> Option<Rect> {
+        Some(Rect::new(self.position.0, self.position.1, self.width, self.height))
+    }
+
+    fn width(&self) -> f32 {
+        self.width
+    }
+
+    fn height(&self) -> f32 {
+        self.height
+    }
+
+    fn bg(&self) -> Color {
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.text_area);
+        self.ring = theme.palette.accent;
+        *self.wrapping.get_mut() = None;
+    }
+
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let size = constraints.constrain(self.preferred);
+        self.width = size.width;
+        self.height = size.height;
+        size
+    }
+
+    fn drain_events(&mut self, events: &mut Vec<Event>) {
+        events.append(&mut self.events);
+    }
+
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        self.position = (x, y);
+        self.events.clear();
+        let previous_text = self.get_text();
+        let previous_cursor = self.editor.cursor();
+
+        let clicked = input.is_mouse_button_pressed(MouseButton::Left);
+        self.hover = input.hit_test(x, y, self.width, self.height);
+        self.just_clicked = self.hover && clicked;
+        if self.hover || self.dragging {
+            input.consume_mouse();
+        }
+
+        if self.just_clicked {
+            self.selected = true;
+        }
+        else if clicked && !self.hover {
+            self.selected = false;
+        }
+
+        if self.selected && input.is_key_pressed(KeyCode::Escape) {
+            self.selected = false;
+        }
+
+        if self.hover {
+            let wheel = input.take_mouse_wheel().1;
+            let first_line = self.first_line.get();
+            if wheel > 0.0 {
+                self.first_line.set(first_line.saturating_sub(WHEEL_LINES));
+            } else if wheel < 0.0 {
+                self.first_line.set(first_line + WHEEL_LINES);
+            }
+        }
+
+        let (mouse_x, mouse_y) = input.mouse_position();
+        let (mouse_x, mouse_y) = (mouse_x - x, mouse_y - y);
+        if self.just_clicked {
+            if input.time - self.last_click <= DOUBLE_CLICK_TIME {
+                self.editor.select_word_at(self.offset_at(mouse_x, mouse_y).0);
+                self.last_click = f64::NEG_INFINITY;
+            } else {
+                self.place_cursor(self.offset_at(mouse_x, mouse_y), input.is_shift_down());
+                self.dragging = true;
+                self.last_click = input.time;
+            }
+        }
+        if self.dragging {
+            if input.is_mouse_button_down(MouseButton::Left) {
+                self.place_cursor(self.offset_at(mouse_x, mouse_y), true);
+            } else {
+                self.dragging = false;
+            }
+        }
+
+        // The keyboard belongs to the focused text widget, unless the focus manager used it
+        let typing = self.selected && !input.keyboard_consumed;
+        if self.selected {
+            input.consume_keyboard();
+        }
+        if typing {
+            self.handle_keys(input);
+        }
+
+        let text = self.editor.text();
+        if text != previous_text || (!self.dragging && self.editor.cursor() != previous_cursor) {
+            self.follow_cursor.set(true);
+        }
+        if text != previous_text {
+            self.events.push(Event::TextChanged { id: self.id.clone(), text: text.to_string() });
+            if let Some(on_change) = &mut self.on_change {
+                on_change(text);
+            }
+        }
+    }
+
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let bg = if self.hover || self.selected { self.style.fg } else { self.style.bg };
+        let fg = if self.hover || self.selected { self.style.bg } else { self.style.fg };
+
+        self.style.draw_background(painter, x, y, self.width, self.height, bg);
+
+        let (pad_left, _, pad_top, _) = self.style.padding;
+        let size = self.style.font_size as u16;
+        let line_height = self.line_height();
+        let text = self.editor.text();
+        let wrapping = self.wrapping();
+        let (gutter, lines) = (wrapping.gutter, &wrapping.lines);
+
+        // Scroll so the cursor is visible if it moved, and never past the last line
+        let visible = self.visible_lines();
+        let mut first_line = self.first_line.get();
+        if self.follow_cursor.replace(false) {
+            let cursor_line = self.cursor_line(lines);
+            if cursor_line < first_line {
+                first_line = cursor_line;
+            } else if cursor_line >= first_line + visible {
+                first_line = cursor_line + 1 - visible;
+            }
+        }
+        let first_line = first_line.min(lines.len().saturating_sub(visible));
+        self.first_line.set(first_line);
+
+        let ascent = painter.measure_text("Ay", self.style.font.as_ref(), size).offset_y;
+        let text_x = x + pad_left + gutter;
+        let selection = self.editor.selection();
+        let cursor_line = self.cursor_line(lines);
+
+        for (i, line) in lines.iter().enumerate().skip(first_line).take(visible) {
+            let top = y + pad_top + (i - first_line) as f32 * line_height;
+            let baseline = top + (line_height - self.style.font_size) / 2.0 + ascent;
+
+            if let Some(selection) = &selection && selection.start <= line.end() && selection.end > line.start() {
+                let x1 = line.x_of(selection.start.max(line.start()));
+                let mut x2 = line.x_of(selection.end.min(line.end()));
+                if selection.end > line.end() {
+                    // Show that the newline at the end of the line is selected too
+                    x2 += self.style.font_size * 0.25;
+                }
+                painter.draw_rectangle(text_x + x1, top, x2 - x1, line_height, Color { a: 0.35, ..fg });
+            }
+
+            if self.line_numbers && lines.get(i.wrapping_sub(1)).is_none_or(|previous| previous.line != line.line) {
+                let number = (line.line + 1).to_string();
+                let number_width = painter.measure_text(&number, self.style.font.as_ref(), size).width;
+                painter.draw_text(&number, text_x - 8.0 - number_width, baseline, self.style.font.as_ref(), size, Color { a: 0.5, ..fg });
+            }
+
+            painter.draw_text(&text[line.start()..line.end()], text_x, baseline, self.style.font.as_ref(), size, fg);
+
+            if i == cursor_line && self.selected {
+                let cursor_x = text_x + line.x_of(self.editor.cursor());
+                painter.draw_line(cursor_x, top, cursor_x, top + line_height, 2.0, fg);
+            }
+        }
+
+        if self.line_numbers {
+            painter.draw_line(text_x - 4.0, y, text_x - 4.0, y + self.height, 2.0, Color { a: 0.5, ..fg });
+        }
+        self.style.draw_border(painter, x, y, self.width, self.height, fg);
+
+        if self.selected {
+            self.style.draw_focus_ring(painter, x, y, self.width, self.height, self.ring);
+        }
+    }
+}
+
+impl Action for TextArea {
+    fn is_clicked(&self) -> bool {
+        self.just_clicked
+    }
+
+    fn is_hovered(&self) -> bool {
+        self.hover
+    }
+}
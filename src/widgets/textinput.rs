@@ -1,62 +1,603 @@
-//! This module defines the [`TextInput`] widget that allows the user to enter text.
+//! This module defines the [`TextInput`] widget that allows the user to enter text, and the
+//! [`TextInputBuilder`] which makes one.
 use std::any::Any;
+use std::cell::RefCell;
 
 use macroquad::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::editor::TextEditor;
+use crate::event::Event;
+use crate::input::{InputState, DOUBLE_CLICK_TIME};
+use crate::layout::{Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::{mix, Style, StyleOverride, Theme, DISABLED_FADE};
 
 use super::widget::{Action, Widget};
 
-fn truncate_text_to_fit(text: &str, max_width: f32, font: Option<&Font>, size: u16) -> String {
-    let mut result = String::new();
+pub(crate) type TextCallback = Box<dyn FnMut(&str)>;
+pub(crate) type CharFilter = Box<dyn Fn(char) -> bool>;
+type Validator = Box<dyn Fn(&str) -> bool>;
+
+/// Works out which part of the text fits in `max_width` and returns the byte offset and width of
+/// every grapheme boundary in it, starting with `(start, 0.0)`.
+///
+/// The end of the text is shown, unless the cursor is in front of it, then the text is shown from
+/// the cursor onwards.
+fn fit_text(painter: &dyn Painter, text: &str, cursor: usize, max_width: f32, font: Option<&Font>, size: u16) -> Vec<(usize, f32)> {
+    let graphemes = text.grapheme_indices(true)
+        .map(|(i, g)| (i, g.len(), painter.measure_text(g, font, size).width))
+        .collect::<Vec<_>>();
+
+    let mut start = graphemes.len();
     let mut total_width = 0.0;
+    while start > 0 && total_width + graphemes[start - 1].2 <= max_width {
+        start -= 1;
+        total_width += graphemes[start].2;
+    }
+    if start < graphemes.len() && cursor < graphemes[start].0 {
+        start = graphemes.iter().rposition(|(i, _, _)| *i <= cursor).unwrap_or(0);
+    }
 
-    for c in text.chars().rev() {
-        let next_width = measure_text(&c.to_string(), font, size, 1.0).width;
-        if total_width + next_width > max_width {
+    let mut boundaries = vec![(graphemes.get(start).map_or(text.len(), |g| g.0), 0.0)];
+    let mut total_width = 0.0;
+    for (i, len, width) in &graphemes[start..] {
+        if total_width + width > max_width {
             break;
         }
-        result.insert(0, c); // Insert at beginning since we’re iterating in reverse
-        total_width += next_width;
+        total_width += width;
+        boundaries.push((i + len, total_width));
     }
+    boundaries
+}
+
+/// Returns if `key` was pressed this frame, or is held down and the key repeat `cooldown` ran out.
+///
+/// Keys act as soon as they're pressed and then repeat every few frames while held.
+pub(crate) fn is_key_repeated(input: &InputState, cooldown: u32, key: KeyCode) -> bool {
+    input.is_key_pressed(key) || (cooldown == 0 && input.is_key_down(key))
+}
+
+/// The character used to hide the text of a password field.
+const BULLET: char = '•';
+
+/// Returns the byte offset in the masked text of a password field which matches `offset` in
+/// `text`.
+fn mask_offset(text: &str, offset: usize) -> usize {
+    text[..offset].graphemes(true).count() * BULLET.len_utf8()
+}
+
+/// Returns the byte offset in `text` which matches `offset` in the masked text of a password
+/// field.
+fn unmask_offset(text: &str, offset: usize) -> usize {
+    text.grapheme_indices(true).nth(offset / BULLET.len_utf8()).map_or(text.len(), |(i, _)| i)
+}
 
-    result
+/// The rules a text widget applies to the text the user types or pastes.
+#[derive(Default)]
+pub(crate) struct EditOptions {
+    /// Newlines are kept when pasting.
+    pub(crate) multiline: bool,
+    /// The text can't be copied or cut to the clipboard.
+    pub(crate) secret: bool,
+    /// Only characters for which the filter returns true are inserted.
+    pub(crate) filter: Option<CharFilter>,
+    /// The most graphemes the text may hold.
+    pub(crate) max_length: Option<usize>,
+}
+
+impl EditOptions {
+    /// Returns the part of `text` which may be inserted into `editor`, without the characters
+    /// which are filtered out and the graphemes which don't fit in the max length.
+    fn accept(&self, editor: &TextEditor, text: &str) -> String {
+        let text = text.chars()
+            .filter(|c| (self.multiline && *c == '\n') || !c.is_control())
+            .filter(|c| self.filter.as_ref().is_none_or(|filter| filter(*c)))
+            .collect::<String>();
+        match self.max_length {
+            Some(max_length) => {
+                let room = max_length.saturating_sub(editor.len() - editor.selected_text().graphemes(true).count());
+                text.graphemes(true).take(room).collect()
+            }
+            None => text,
+        }
+    }
+}
+
+/// Applies the editing keys, shortcuts and typed characters shared by the text widgets to
+/// `editor`, following `options`. Returns if a repeating key did anything.
+pub(crate) fn handle_editing_keys(editor: &mut TextEditor, input: &mut InputState, cooldown: u32, options: &EditOptions) -> bool {
+    let ctrl = input.is_ctrl_down();
+    let shift = input.is_shift_down();
+
+    let repeat = |key| is_key_repeated(input, cooldown, key);
+    let edited = (repeat(KeyCode::Backspace) && editor.backspace()) ||
+        (repeat(KeyCode::Delete) && editor.delete()) ||
+        (repeat(KeyCode::Left) && if ctrl { editor.move_word_left(shift) } else { editor.move_left(shift) }) ||
+        (repeat(KeyCode::Right) && if ctrl { editor.move_word_right(shift) } else { editor.move_right(shift) });
+
+    if ctrl {
+        if input.is_key_pressed(KeyCode::A) {
+            editor.select_all();
+        }
+        if input.is_key_pressed(KeyCode::Z) && !shift {
+            editor.undo();
+        }
+        if (input.is_key_pressed(KeyCode::Z) && shift) || input.is_key_pressed(KeyCode::Y) {
+            editor.redo();
+        }
+        if (input.is_key_pressed(KeyCode::C) || input.is_key_pressed(KeyCode::X)) && editor.selection().is_some() && !options.secret {
+            input.clipboard.set(editor.selected_text());
+            if input.is_key_pressed(KeyCode::X) {
+                editor.delete_selection();
+            }
+        }
+        if input.is_key_pressed(KeyCode::V) && let Some(text) = input.clipboard.get() {
+            let text = options.accept(editor, &text);
+            if !text.is_empty() {
+                editor.insert_str(&text);
+            }
+        }
+        // Shortcuts also send their letters as characters on some platforms
+        input.take_chars();
+    } else {
+        // Control characters like backspace or enter are sent as characters on some platforms,
+        // they're handled as keys above instead
+        let typed = input.take_chars().into_iter().filter(|c| !c.is_control()).collect::<String>();
+        for c in options.accept(editor, &typed).chars() {
+            editor.insert_char(c);
+        }
+    }
+
+    edited
 }
 
 /// The [`TextInput`] widget that allows the user to enter text.
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut textinput = TextInput::new(200.0, 40.0, BLACK, WHITE, None);
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(10.0, 10.0).click(MouseButton::Left)
+///     .type_text("naïve 🦀").tap_key(KeyCode::Left).tap_key(KeyCode::Backspace);
+///
+/// while !input.is_finished() {
+///     textinput.update(0.0, 0.0, &mut input.poll());
+/// }
+/// assert_eq!(textinput.get_text(), "naïve🦀");
+/// ```
+///
+/// Text can be selected with the mouse, Shift and the arrow keys or Ctrl+A, and moved through the
+/// clipboard with Ctrl+C, Ctrl+X and Ctrl+V. Edits are undone with Ctrl+Z and redone with
+/// Ctrl+Shift+Z or Ctrl+Y. Clicking relies on the layout of the last render.
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut textinput = TextInput::new(200.0, 40.0, BLACK, WHITE, None);
+/// textinput.set_text("hello world".to_string());
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(60.0, 20.0).click(MouseButton::Left).click(MouseButton::Left)
+///     .press_key(KeyCode::LeftControl).tap_key(KeyCode::X).release_key(KeyCode::LeftControl)
+///     .tap_key(KeyCode::End).press_key(KeyCode::LeftControl).tap_key(KeyCode::V)
+///     .tap_key(KeyCode::Z).tap_key(KeyCode::Z).tap_key(KeyCode::Y);
+///
+/// while !input.is_finished() {
+///     textinput.update(0.0, 0.0, &mut input.poll());
+///     textinput.render(0.0, 0.0, &mut RecordingPainter::new());
+/// }
+/// assert_eq!(textinput.get_text(), " world");
+/// assert_eq!(input.clipboard().as_deref(), Some("hello"));
+/// ```
+///
+/// Filters and a max length limit what the user can type or paste, while a validator only marks
+/// the [`TextInput`] as invalid. Password fields show bullets instead of the text.
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let mut pin = TextInput::new(200.0, 40.0, BLACK, WHITE, None)
+///     .numeric()
+///     .with_max_length(4)
+///     .with_validator(|text| text.len() == 4)
+///     .with_password(true)
+///     .with_placeholder("PIN".to_string());
+/// let mut input = ScriptedInput::new();
+/// input.move_mouse(10.0, 10.0).click(MouseButton::Left).type_text("12a3");
+///
+/// while !input.is_finished() {
+///     pin.update(0.0, 0.0, &mut input.poll());
+/// }
+/// assert_eq!(pin.get_text(), "123");
+/// assert!(!pin.is_valid());
+///
+/// input.type_text("456");
+/// while !input.is_finished() {
+///     pin.update(0.0, 0.0, &mut input.poll());
+/// }
+/// assert_eq!(pin.get_text(), "1234");
+/// assert!(pin.is_valid());
+///
+/// let mut painter = RecordingPainter::new();
+/// pin.render(0.0, 0.0, &mut painter);
+/// assert!(painter.commands().iter().any(|c| matches!(c, DrawCommand::Text { text, .. } if text == "••••")));
+/// ```
 pub struct TextInput {
-    text: String,
-    pos: u32,
-    bg: Color,
-    fg: Color,
+    editor: TextEditor,
+    style: Style,
+    overrides: StyleOverride,
+    /// The color of the border while the text isn't valid.
+    invalid: Color,
+    /// The color of the focus ring.
+    ring: Color,
     hover: bool,
     just_clicked: bool,
     selected: bool,
-    font: Option<Font>,
+    position: (f32, f32),
     width: f32,
     height: f32,
     cooldown: u32,
+    dragging: bool,
+    last_click: f64,
+    carets: RefCell<Vec<(usize, f32)>>,
+    options: EditOptions,
+    validator: Option<Validator>,
+    valid: bool,
+    placeholder: Option<String>,
+    enabled: bool,
+    /// If the containers around the widget are enabled.
+    inherited_enabled: bool,
+    preferred: Size,
+    id: Option<String>,
+    events: Vec<Event>,
+    on_change: Option<TextCallback>,
+    on_submit: Option<TextCallback>,
 }
 
 impl TextInput {
-    /// Creates a new [`TextInput`] widget.
+    /// Creates a new [`TextInput`] widget with the specified colors and font, which are kept when
+    /// it's restyled. The text is sized from the height.
     pub fn new(width: f32, height: f32, bg: Color, fg: Color, font: Option<Font>) -> Self {
+        Self::themed(width, height).with_style(StyleOverride {
+            bg: Some(bg),
+            fg: Some(fg),
+            font,
+            font_size: Some(height * 0.4),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new [`TextInput`] widget styled by the global [`Theme`].
+    pub fn themed(width: f32, height: f32) -> Self {
+        let theme = Theme::global();
         Self {
-            text: String::new(),
-            pos: 0,
-            bg,
-            fg,
+            editor: TextEditor::new(),
+            style: theme.text_input,
+            overrides: StyleOverride::new(),
+            invalid: theme.palette.error,
+            ring: theme.palette.accent,
             hover: false,
             just_clicked: false,
             selected: false,
-            font,
+            position: (0.0, 0.0),
             width,
             height,
             cooldown: 0,
+            dragging: false,
+            last_click: f64::NEG_INFINITY,
+            carets: RefCell::new(Vec::new()),
+            options: EditOptions::default(),
+            validator: None,
+            valid: true,
+            placeholder: None,
+            enabled: true,
+            inherited_enabled: true,
+            preferred: Size::new(width, height),
+            id: None,
+            events: Vec::new(),
+            on_change: None,
+            on_submit: None,
         }
     }
 
+    /// Returns a [`TextInputBuilder`] for an empty 300 by 50 text input, styled by the global
+    /// [`Theme`].
+    pub fn builder() -> TextInputBuilder {
+        TextInputBuilder {
+            input: TextInput::themed(300.0, 50.0),
+        }
+    }
+
+    /// Overrides part of the style of the [`TextInput`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self
+    }
+
+    /// Enables or disables the [`TextInput`] widget. A disabled text input is dimmed, can't take the focus
+    /// and ignores the mouse and the keyboard, so it emits no events.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Enables or disables the [`TextInput`] widget, see [`TextInput::with_enabled`].
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Sets the ID of the [`TextInput`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets a callback which is called with the new text every time the user edits it.
+    pub fn on_change(mut self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.on_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback which is called with the text every time Enter is pressed while the
+    /// [`TextInput`] is selected.
+    pub fn on_submit(mut self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.on_submit = Some(Box::new(callback));
+        self
+    }
+
+    /// Only lets the user type or paste characters for which `filter` returns true.
+    pub fn with_filter(mut self, filter: impl Fn(char) -> bool + 'static) -> Self {
+        self.options.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Only lets the user type or paste the digits 0 to 9.
+    pub fn numeric(self) -> Self {
+        self.with_filter(|c| c.is_ascii_digit())
+    }
+
+    /// Limits the text the user can type or paste to `max_length` graphemes.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.options.max_length = Some(max_length);
+        self
+    }
+
+    /// Sets a validator which is run on the text after every edit. While it returns false, the
+    /// [`TextInput`] is drawn with a border in the error color of the [`Theme`] and
+    /// [`TextInput::is_valid`] returns false.
+    pub fn with_validator(mut self, validator: impl Fn(&str) -> bool + 'static) -> Self {
+        self.valid = validator(self.editor.text());
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    /// Hides the text behind bullets and keeps it from being copied or cut, for passwords.
+    pub fn with_password(mut self, password: bool) -> Self {
+        self.options.secret = password;
+        self
+    }
+
+    /// Sets the text shown while the [`TextInput`] is empty.
+    pub fn with_placeholder(mut self, placeholder: String) -> Self {
+        self.placeholder = Some(placeholder);
+        self
+    }
+
+    /// Returns if the validator accepts the current text, which is always true without a
+    /// validator.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
     /// Returns the text entered in the [`TextInput`] widget.
     pub fn get_text(&self) -> String {
-        self.text.clone()
+        self.editor.text().to_string()
+    }
+
+    /// Replaces the text in the [`TextInput`] widget and moves the cursor to its end.
+    ///
+    /// The text isn't filtered or limited to the max length, but it is validated.
+    pub fn set_text(&mut self, text: String) {
+        self.editor.set_text(text);
+        self.validate();
+    }
+
+    fn validate(&mut self) {
+        self.valid = self.validator.as_ref().is_none_or(|validator| validator(self.editor.text()));
+    }
+
+    /// Clears the text in the [`TextInput`] widget.
+    pub fn clear(&mut self) {
+        self.set_text(String::new());
+    }
+
+    /// Returns the selected text in the [`TextInput`] widget, which is empty if nothing is
+    /// selected.
+    pub fn get_selected_text(&self) -> String {
+        self.editor.selected_text().to_string()
+    }
+
+    /// Reverts the last edit, returns if there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let changed = self.editor.undo();
+        if changed {
+            self.validate();
+        }
+        changed
+    }
+
+    /// Re-applies the last undone edit, returns if there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let changed = self.editor.redo();
+        if changed {
+            self.validate();
+        }
+        changed
+    }
+
+    /// Forgets every edit, so they can't be undone or redone anymore.
+    pub fn clear_history(&mut self) {
+        self.editor.clear_history();
+    }
+
+    /// Returns the byte offset of the grapheme boundary closest to `x`, relative to the left of
+    /// the widget, using the layout of the last render.
+    fn offset_at(&self, x: f32) -> usize {
+        self.carets.borrow()
+            .iter()
+            .min_by(|a, b| (a.1 - x).abs().total_cmp(&(b.1 - x).abs()))
+            .map_or(self.editor.text().len(), |caret| caret.0)
+    }
+
+    fn handle_keys(&mut self, input: &mut InputState) {
+        let shift = input.is_shift_down();
+
+        if handle_editing_keys(&mut self.editor, input, self.cooldown, &self.options) {
+            self.cooldown = 2;
+        } else if self.cooldown > 0 {
+            self.cooldown -= 1;
+        }
+
+        if input.is_key_pressed(KeyCode::Home) {
+            self.editor.move_home(shift);
+        }
+        if input.is_key_pressed(KeyCode::End) {
+            self.editor.move_end(shift);
+        }
+    }
+}
+
+/// The [`TextInputBuilder`] struct makes a [`TextInput`] one setting at a time, made with
+/// [`TextInput::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let age = TextInput::builder()
+///     .placeholder("Age".to_string())
+///     .numeric()
+///     .max_length(3)
+///     .validator(|text| !text.is_empty())
+///     .on_submit(|text| println!("{} years old", text))
+///     .build();
+/// assert!(!age.is_valid());
+/// ```
+pub struct TextInputBuilder {
+    input: TextInput,
+}
+
+impl TextInputBuilder {
+    /// Sets the size of the text input.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.input.width = width;
+        self.input.height = height;
+        self.input.preferred = Size::new(width, height);
+        self
+    }
+
+    /// Sets the text the text input starts with.
+    pub fn text(mut self, text: String) -> Self {
+        self.input.set_text(text);
+        self
+    }
+
+    /// Sets the text shown while the text input is empty.
+    pub fn placeholder(mut self, placeholder: String) -> Self {
+        self.input = self.input.with_placeholder(placeholder);
+        self
+    }
+
+    /// Hides the text behind bullets, for passwords, see [`TextInput::with_password`].
+    pub fn password(mut self, password: bool) -> Self {
+        self.input = self.input.with_password(password);
+        self
+    }
+
+    /// Only lets the user type or paste characters for which `filter` returns true.
+    pub fn filter(mut self, filter: impl Fn(char) -> bool + 'static) -> Self {
+        self.input = self.input.with_filter(filter);
+        self
+    }
+
+    /// Only lets the user type or paste the digits 0 to 9.
+    pub fn numeric(mut self) -> Self {
+        self.input = self.input.numeric();
+        self
+    }
+
+    /// Limits the text the user can type or paste to `max_length` graphemes.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.input = self.input.with_max_length(max_length);
+        self
+    }
+
+    /// Sets a validator which is run on the text after every edit, see
+    /// [`TextInput::with_validator`].
+    pub fn validator(mut self, validator: impl Fn(&str) -> bool + 'static) -> Self {
+        self.input = self.input.with_validator(validator);
+        self
+    }
+
+    /// Enables or disables the text input, see [`TextInput::with_enabled`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.input = self.input.with_enabled(enabled);
+        self
+    }
+
+    /// Sets the ID of the text input, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.input = self.input.with_id(id);
+        self
+    }
+
+    /// Overrides part of the style of the text input, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.input = self.input.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the text input.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Sets the foreground color of the text input.
+    pub fn fg(self, fg: Color) -> Self {
+        self.style(StyleOverride::new().fg(fg))
+    }
+
+    /// Sets the font of the text input.
+    pub fn font(self, font: Font) -> Self {
+        self.style(StyleOverride::new().font(font))
+    }
+
+    /// Sets the font size of the text input.
+    pub fn font_size(self, font_size: f32) -> Self {
+        self.style(StyleOverride::new().font_size(font_size))
+    }
+
+    /// Sets a callback which is called with the new text every time the user edits it.
+    pub fn on_change(mut self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.input = self.input.on_change(callback);
+        self
+    }
+
+    /// Sets a callback which is called with the text every time Enter is pressed while the text
+    /// input is selected.
+    pub fn on_submit(mut self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.input = self.input.on_submit(callback);
+        self
+    }
+
+    /// Makes the [`TextInput`].
+    pub fn build(self) -> TextInput {
+        self.input
     }
 }
 
@@ -65,6 +606,42 @@ impl Widget for TextInput {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.is_enabled()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled && self.inherited_enabled
+    }
+
+    fn set_inherited_enabled(&mut self, enabled: bool) {
+        self.inherited_enabled = enabled;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.selected
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.selected = focused;
+    }
+
+    fn edits_text(&self) -> bool {
+        true
+    }
+
+    fn screen_rect(&self) -> Option<Rect> {
+        Some(Rect::new(self.position.0, self.position.1, self.width, self.height))
+    }
+
     fn width(&self) -> f32 {
         self.width
     }
@@ -74,17 +651,49 @@ impl Widget for TextInput {
     }
 
     fn bg(&self) -> Color {
-        self.bg
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.text_input);
+        self.invalid = theme.palette.error;
+        self.ring = theme.palette.accent;
+    }
+
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let size = constraints.constrain(self.preferred);
+        self.width = size.width;
+        self.height = size.height;
+        size
     }
 
-    fn update(&mut self, x: f32, y: f32) {
-        let mouse_pos = mouse_position();
-        let mx = mouse_pos.0;
-        let my = mouse_pos.1;
-        
-        let clicked = is_mouse_button_pressed(MouseButton::Left);
-        self.hover = mx >= x && mx <= x + self.width && my >= y && my <= y + self.height;
+    fn drain_events(&mut self, events: &mut Vec<Event>) {
+        events.append(&mut self.events);
+    }
+
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        self.position = (x, y);
+        if !self.is_enabled() {
+            self.hover = false;
+            self.just_clicked = false;
+            self.selected = false;
+            self.dragging = false;
+            self.events.clear();
+            // A disabled text input still covers whatever is under it
+            if input.hit_test(x, y, self.width, self.height) {
+                input.consume_mouse();
+            }
+            return;
+        }
+        self.events.clear();
+        let previous_text = self.get_text();
+
+        let clicked = input.is_mouse_button_pressed(MouseButton::Left);
+        self.hover = input.hit_test(x, y, self.width, self.height);
         self.just_clicked = self.hover && clicked;
+        if self.hover || self.dragging {
+            input.consume_mouse();
+        }
 
         if self.just_clicked {
             self.selected = true;
@@ -93,86 +702,127 @@ impl Widget for TextInput {
             self.selected = false;
         }
 
-        if self.selected && is_key_pressed(KeyCode::Escape) {
+        if self.selected && input.is_key_pressed(KeyCode::Escape) {
             self.selected = false;
         }
 
-        let mut operation_used = false;
-        
-        if self.cooldown == 0 {
-            if self.selected && is_key_down(KeyCode::Backspace) {
-                if self.pos > 0 {
-                    self.text.remove(self.pos as usize - 1);
-                    self.pos -= 1;
-                    self.cooldown = 2;
-                }
-            }
-            if self.selected && is_key_down(KeyCode::Delete) {
-                if self.pos < self.text.len() as u32 {
-                    self.text.remove(self.pos as usize);
-                    self.cooldown = 2;
-                }
-            }
-            if self.selected && is_key_down(KeyCode::Left) {
-                if self.pos > 0 {
-                    self.pos -= 1;
-                    self.cooldown = 2;
+        let mouse_x = input.mouse_position().0 - x;
+        if self.just_clicked {
+            if input.time - self.last_click <= DOUBLE_CLICK_TIME {
+                if self.options.secret {
+                    // Selecting a word would give away where the spaces are
+                    self.editor.select_all();
+                } else {
+                    self.editor.select_word_at(self.offset_at(mouse_x));
                 }
+                self.last_click = f64::NEG_INFINITY;
+            } else {
+                self.editor.set_cursor(self.offset_at(mouse_x), input.is_shift_down());
+                self.dragging = true;
+                self.last_click = input.time;
             }
-            if self.selected && is_key_down(KeyCode::Right) {
-                if self.pos < self.text.len() as u32 {
-                    self.pos += 1;
-                    self.cooldown = 2;
-                }
+        }
+        if self.dragging {
+            if input.is_mouse_button_down(MouseButton::Left) {
+                self.editor.set_cursor(self.offset_at(mouse_x), true);
+            } else {
+                self.dragging = false;
             }
-        } else if self.cooldown > 0 {
-            self.cooldown -= 1;
         }
 
-        if is_key_down(KeyCode::Backspace) || is_key_down(KeyCode::Delete) || is_key_down(KeyCode::Left) || is_key_down(KeyCode::Right) {
-            operation_used = true;
-        }
-        
+        // The keyboard belongs to the focused text widget, unless the focus manager used it
+        let typing = self.selected && !input.keyboard_consumed;
         if self.selected {
-            while let Some(key) = get_char_pressed() {
-                if operation_used {
-                    return;
-                }
-                self.text.insert(self.pos as usize, key);
-                self.pos += 1;
+            input.consume_keyboard();
+        }
+        if typing {
+            self.handle_keys(input);
+        }
+
+        if self.editor.text() != previous_text {
+            self.validate();
+        }
+
+        let text = self.editor.text();
+        if text != previous_text {
+            self.events.push(Event::TextChanged { id: self.id.clone(), text: text.to_string() });
+            if let Some(on_change) = &mut self.on_change {
+                on_change(text);
+            }
+        }
+
+        if typing && (input.is_key_pressed(KeyCode::Enter) || input.is_key_pressed(KeyCode::KpEnter)) {
+            self.events.push(Event::Submitted { id: self.id.clone(), text: text.to_string() });
+            if let Some(on_submit) = &mut self.on_submit {
+                on_submit(text);
             }
         }
     }
 
-    fn render(&self, x: f32, y: f32) {
-        let bg = if self.hover || self.selected { self.fg } else { self.bg };
-        let fg = if self.hover || self.selected { self.bg } else { self.fg };
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let bg = if self.hover || self.selected { self.style.fg } else { self.style.bg };
+        let fg = if self.hover || self.selected { self.style.bg } else { self.style.fg };
+        let fg = if self.is_enabled() { fg } else { mix(fg, bg, DISABLED_FADE) };
 
-        draw_rectangle(x, y, self.width, self.height, bg);
-        
-        let size = (self.height * 0.4) as u16;
-        let visible_text = truncate_text_to_fit(&self.text, self.width - 8.0, self.font.as_ref(), size);
-        let text_size = measure_text(&visible_text, self.font.as_ref(), size, 1.0);
+        self.style.draw_background(painter, x, y, self.width, self.height, bg);
 
-        draw_text_ex(&visible_text,
-            x + self.width / 2.0 - text_size.width / 2.0,
-            y + self.height / 2.0 + text_size.height / 4.0,
-            TextParams {
-                font: self.font.as_ref(),
-                font_size: size,
-                font_scale: 1.0,
-                color: fg,
-                ..Default::default()
+        let (pad_left, pad_right, pad_top, pad_bottom) = self.style.padding;
+        let inner_width = self.width - pad_left - pad_right;
+        let size = self.style.font_size as u16;
+        let text = self.editor.text();
+        let masked;
+        let (shown, cursor) = if self.options.secret {
+            masked = BULLET.to_string().repeat(text.graphemes(true).count());
+            (masked.as_str(), mask_offset(text, self.editor.cursor()))
+        } else {
+            (text, self.editor.cursor())
+        };
+        let mut boundaries = fit_text(painter, shown, cursor, inner_width, self.style.font.as_ref(), size);
+        let visible_text = &shown[boundaries[0].0..boundaries[boundaries.len() - 1].0];
+        if self.options.secret {
+            for boundary in &mut boundaries {
+                boundary.0 = unmask_offset(text, boundary.0);
             }
+        }
+        let (start, end) = (boundaries[0].0, boundaries[boundaries.len() - 1].0);
+        let text_size = painter.measure_text(visible_text, self.style.font.as_ref(), size);
+        let text_x = pad_left + inner_width / 2.0 - text_size.width / 2.0;
+        let caret_x = |offset: usize| {
+            let offset = offset.clamp(start, end);
+            boundaries.iter().find(|b| b.0 >= offset).map_or(0.0, |b| b.1) + text_x
+        };
+
+        if let Some(selection) = self.editor.selection() {
+            let (x1, x2) = (caret_x(selection.start), caret_x(selection.end));
+            painter.draw_rectangle(x + x1, y + pad_top, x2 - x1, self.height - pad_top - pad_bottom, Color { a: 0.35, ..fg });
+        }
+
+        painter.draw_text(visible_text,
+            x + text_x,
+            y + self.height / 2.0 + text_size.height / 4.0,
+            self.style.font.as_ref(), size, fg
         );
-        
-        let text_behind_cursor = self.text.chars().take(self.pos as usize).collect::<String>();
-        let text_behind_cursor = truncate_text_to_fit(&text_behind_cursor, self.width - 8.0, self.font.as_ref(), size);
-        let text_behind_cursor_size = measure_text(&text_behind_cursor, self.font.as_ref(), size, 1.0);
-        let cursor_x = x + text_behind_cursor_size.width + 4.0 + self.width / 2.0 - text_size.width / 2.0;
-        draw_line(cursor_x, y + 8.0, cursor_x, y + self.height - 8.0, 3.0, fg);
-
-        draw_rectangle_lines(x, y, self.width, self.height, 4.0, fg);
+
+        if let Some(placeholder) = self.placeholder.as_ref().filter(|_| text.is_empty()) {
+            let placeholder_size = painter.measure_text(placeholder, self.style.font.as_ref(), size);
+            painter.draw_text(placeholder,
+                x + pad_left + inner_width / 2.0 - placeholder_size.width / 2.0,
+                y + self.height / 2.0 + placeholder_size.height / 4.0,
+                self.style.font.as_ref(), size, Color { a: 0.5, ..fg }
+            );
+        }
+
+        let cursor_x = x + caret_x(self.editor.cursor());
+        painter.draw_line(cursor_x, y + pad_top, cursor_x, y + self.height - pad_bottom, 3.0, fg);
+
+        *self.carets.borrow_mut() = boundaries.iter().map(|(offset, width)| (*offset, width + text_x)).collect();
+
+        let border = if self.valid { fg } else { self.invalid };
+        self.style.draw_border(painter, x, y, self.width, self.height, border);
+
+        if self.selected {
+            self.style.draw_focus_ring(painter, x, y, self.width, self.height, self.ring);
+        }
     }
 }
 
@@ -1,38 +1,215 @@
-//! This module defines the [`Toggle`] widget that can be toggled on and off.
+//! This module defines the [`Toggle`] widget that can be toggled on and off, and the
+//! [`ToggleBuilder`] which makes one.
 use std::any::Any;
 
 use macroquad::prelude::*;
 
+use crate::event::Event;
+use crate::input::InputState;
+use crate::layout::{Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::{mix, Style, StyleOverride, Theme, DISABLED_FADE};
+
 use super::widget::{Widget, Action};
 
-/// A [`Toggle`] widget that once clicked, toggles its state between on and off.
+/// A [`Toggle`] widget that once clicked, toggles its state between on and off. While it has the
+/// keyboard focus, the confirm action of the [`FocusManager`](crate::focus::FocusManager) toggles
+/// it too.
 pub struct Toggle {
     width: f32,
     height: f32,
     text: String,
-    bg: Color,
-    fg: Color,
+    style: Style,
+    overrides: StyleOverride,
+    /// The color of the focus ring.
+    ring: Color,
     hover: bool,
     toggle: bool,
     just_clicked: bool,
-    font: Option<Font>,
+    focused: bool,
+    activated: bool,
+    position: (f32, f32),
+    enabled: bool,
+    /// If the containers around the widget are enabled.
+    inherited_enabled: bool,
+    preferred: Size,
+    id: Option<String>,
+    events: Vec<Event>,
+    on_change: Option<Box<dyn FnMut(bool)>>,
 }
 
 impl Toggle {
-    /// Creates a new [`Toggle`] widget.
+    /// Creates a new [`Toggle`] widget with the specified colors and font, which are kept when it's
+    /// restyled. The text is sized from the height.
     pub fn new(width: f32, height: f32, text: String, bg: Color, fg: Color, font: Option<Font>) -> Self {
+        Self::themed(width, height, text).with_style(StyleOverride {
+            bg: Some(bg),
+            fg: Some(fg),
+            font,
+            font_size: Some(height * 0.4),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new [`Toggle`] widget styled by the global [`Theme`].
+    pub fn themed(width: f32, height: f32, text: String) -> Self {
+        let theme = Theme::global();
         Self {
             width,
             height,
             text,
-            bg,
-            fg,
+            style: theme.toggle,
+            overrides: StyleOverride::new(),
+            ring: theme.palette.accent,
             hover: false,
             toggle: false,
             just_clicked: false,
-            font,
+            focused: false,
+            activated: false,
+            position: (0.0, 0.0),
+            enabled: true,
+            inherited_enabled: true,
+            preferred: Size::new(width, height),
+            id: None,
+            events: Vec::new(),
+            on_change: None,
+        }
+    }
+
+    /// Returns a [`ToggleBuilder`] for a 200 by 50 toggle without text which is off, styled by the
+    /// global [`Theme`].
+    pub fn builder() -> ToggleBuilder {
+        ToggleBuilder {
+            toggle: Toggle::themed(200.0, 50.0, String::new()),
         }
     }
+
+    /// Overrides part of the style of the [`Toggle`] widget, which is kept when it's restyled.
+    pub fn with_style(mut self, style: StyleOverride) -> Self {
+        self.style = Theme::with_global(|theme| style.resolve(theme, &self.style));
+        self.overrides = self.overrides.merge(&style);
+        self
+    }
+
+    /// Enables or disables the [`Toggle`] widget. A disabled toggle is dimmed, can't take the focus
+    /// and ignores the mouse and the keyboard, so it emits no events.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Enables or disables the [`Toggle`] widget, see [`Toggle::with_enabled`].
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Sets the ID of the [`Toggle`] widget, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets a callback which is called with the new state every time the [`Toggle`] is toggled.
+    pub fn on_change(mut self, callback: impl FnMut(bool) + 'static) -> Self {
+        self.on_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns if the [`Toggle`] is on.
+    pub fn is_on(&self) -> bool {
+        self.toggle
+    }
+}
+
+/// The [`ToggleBuilder`] struct makes a [`Toggle`] one setting at a time, made with
+/// [`Toggle::builder`].
+///
+/// ```
+/// use macroquad::prelude::*;
+/// use flowquad::prelude::*;
+///
+/// let toggle = Toggle::builder()
+///     .text("Music".to_string())
+///     .on(true)
+///     .on_change(|on| println!("Music is {}", if on { "on" } else { "off" }))
+///     .build();
+/// assert!(toggle.is_on());
+/// ```
+pub struct ToggleBuilder {
+    toggle: Toggle,
+}
+
+impl ToggleBuilder {
+    /// Sets the size of the toggle.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.toggle.width = width;
+        self.toggle.height = height;
+        self.toggle.preferred = Size::new(width, height);
+        self
+    }
+
+    /// Sets the text of the toggle.
+    pub fn text(mut self, text: String) -> Self {
+        self.toggle.text = text;
+        self
+    }
+
+    /// Sets if the toggle starts on.
+    pub fn on(mut self, on: bool) -> Self {
+        self.toggle.toggle = on;
+        self
+    }
+
+    /// Enables or disables the toggle, see [`Toggle::with_enabled`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.toggle = self.toggle.with_enabled(enabled);
+        self
+    }
+
+    /// Sets the ID of the toggle, used to find it with
+    /// [`Container::find`](crate::widgets::container::Container::find).
+    pub fn id(mut self, id: String) -> Self {
+        self.toggle = self.toggle.with_id(id);
+        self
+    }
+
+    /// Overrides part of the style of the toggle, see [`StyleOverride`].
+    pub fn style(mut self, style: StyleOverride) -> Self {
+        self.toggle = self.toggle.with_style(style);
+        self
+    }
+
+    /// Sets the background color of the toggle.
+    pub fn bg(self, bg: Color) -> Self {
+        self.style(StyleOverride::new().bg(bg))
+    }
+
+    /// Sets the foreground color of the toggle.
+    pub fn fg(self, fg: Color) -> Self {
+        self.style(StyleOverride::new().fg(fg))
+    }
+
+    /// Sets the font of the toggle.
+    pub fn font(self, font: Font) -> Self {
+        self.style(StyleOverride::new().font(font))
+    }
+
+    /// Sets the font size of the toggle.
+    pub fn font_size(self, font_size: f32) -> Self {
+        self.style(StyleOverride::new().font_size(font_size))
+    }
+
+    /// Sets a callback which is called with the new state every time the toggle is toggled.
+    pub fn on_change(mut self, callback: impl FnMut(bool) + 'static) -> Self {
+        self.toggle = self.toggle.on_change(callback);
+        self
+    }
+
+    /// Makes the [`Toggle`].
+    pub fn build(self) -> Toggle {
+        self.toggle
+    }
 }
 
 impl Widget for Toggle {
@@ -40,6 +217,42 @@ impl Widget for Toggle {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.is_enabled()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled && self.inherited_enabled
+    }
+
+    fn set_inherited_enabled(&mut self, enabled: bool) {
+        self.inherited_enabled = enabled;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn activate(&mut self) {
+        self.activated = true;
+    }
+
+    fn screen_rect(&self) -> Option<Rect> {
+        Some(Rect::new(self.position.0, self.position.1, self.width, self.height))
+    }
+
     fn width(&self) -> f32 {
         self.width
     }
@@ -49,41 +262,86 @@ impl Widget for Toggle {
     }
 
     fn bg(&self) -> Color {
-        self.bg
+        self.style.bg
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = self.overrides.resolve(theme, &theme.toggle);
+        self.ring = theme.palette.accent;
+    }
+
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let size = constraints.constrain(self.preferred);
+        self.width = size.width;
+        self.height = size.height;
+        size
     }
 
-    fn update(&mut self, x: f32, y: f32) {
-        let mouse_pos = mouse_position();
-        let mx = mouse_pos.0;
-        let my = mouse_pos.1;
+    fn drain_events(&mut self, events: &mut Vec<Event>) {
+        events.append(&mut self.events);
+    }
 
-        self.hover = mx >= x && mx <= x + self.width && my >= y && my <= y + self.height;
-        self.just_clicked = self.hover && is_mouse_button_pressed(MouseButton::Left);
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState) {
+        self.position = (x, y);
+        if !self.is_enabled() {
+            self.hover = false;
+            self.just_clicked = false;
+            self.activated = false;
+            self.focused = false;
+            self.events.clear();
+            // A disabled toggle still covers whatever is under it
+            if input.hit_test(x, y, self.width, self.height) {
+                input.consume_mouse();
+            }
+            return;
+        }
+        self.hover = input.hit_test(x, y, self.width, self.height);
+        if self.hover {
+            input.consume_mouse();
+        }
+        let pressed = input.is_mouse_button_pressed(MouseButton::Left);
+        if pressed && !self.hover {
+            self.focused = false;
+        }
+        self.just_clicked = (self.hover && pressed) || std::mem::take(&mut self.activated);
         self.toggle = if self.just_clicked { !self.toggle } else { self.toggle };
+
+        self.events.clear();
+        if self.just_clicked {
+            self.events.push(Event::Toggled { id: self.id.clone(), on: self.toggle });
+            if let Some(on_change) = &mut self.on_change {
+                on_change(self.toggle);
+            }
+        }
     }
 
-    fn render(&self, x: f32, y: f32) {
-        let bg = if self.hover || self.toggle { self.fg } else { self.bg };
-        let fg = if self.hover || self.toggle { self.bg } else { self.fg };
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter) {
+        let on = self.hover || self.toggle;
+        let (mut bg, mut fg) = if on { (self.style.fg, self.style.bg) } else { (self.style.bg, self.style.fg) };
+        if !self.is_enabled() {
+            if on {
+                bg = mix(bg, fg, DISABLED_FADE);
+            } else {
+                fg = mix(fg, bg, DISABLED_FADE);
+            }
+        }
+
+        self.style.draw_background(painter, x, y, self.width, self.height, bg);
 
-        draw_rectangle(x, y, self.width, self.height, bg);
-        
-        let size = (self.height * 0.4) as u16;
+        let size = self.style.font_size as u16;
         
-        let text_size = measure_text(&self.text, self.font.as_ref(), size, 1.0);
-        draw_text_ex(&self.text,
+        let text_size = painter.measure_text(&self.text, self.style.font.as_ref(), size);
+        painter.draw_text(&self.text,
             x + self.width / 2.0 - text_size.width / 2.0,
             y + self.height / 2.0 + text_size.height / 4.0,
-            TextParams {
-                font: self.font.as_ref(),
-                font_size: size,
-                font_scale: 1.0,
-                color: fg,
-                ..Default::default()
-            }
+            self.style.font.as_ref(), size, fg
         );
 
-        draw_rectangle_lines(x, y, self.width, self.height, 4.0, fg);
+        self.style.draw_border(painter, x, y, self.width, self.height, fg);
+
+        if self.focused {
+            self.style.draw_focus_ring(painter, x, y, self.width, self.height, self.ring);
+        }
     }
 }
 
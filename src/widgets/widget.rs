@@ -2,20 +2,208 @@
 use macroquad::prelude::*;
 use std::any::Any;
 
+use crate::event::Event;
+use crate::input::InputState;
+use crate::layout::{Constraints, Size};
+use crate::painter::Painter;
+use crate::theme::Theme;
+
 /// The [`Widget`] trait which defines the basic properties and methods for UI elements.
 pub trait Widget: Any {
     /// Returns the type of the widget as an [`Any`] type.
     fn as_any(&self) -> &dyn Any;
+    /// Returns the type of the widget as a mutable [`Any`] type.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Returns the ID of the widget, if it has one.
+    fn id(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the widgets contained in this widget, this is empty for widgets that aren't
+    /// containers.
+    fn children(&self) -> Vec<&dyn Widget> {
+        Vec::new()
+    }
+    /// Returns the widgets contained in this widget mutably.
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        Vec::new()
+    }
+    /// Moves the [`Event`]s queued by this widget and its children into `events`.
+    fn drain_events(&mut self, events: &mut Vec<Event>) {
+        for child in self.children_mut() {
+            child.drain_events(events);
+        }
+    }
+    /// Restyles the widget and its children from the specified [`Theme`], keeping whatever was
+    /// set on each widget explicitly.
+    fn apply_theme(&mut self, theme: &Theme) {
+        for child in self.children_mut() {
+            child.apply_theme(theme);
+        }
+    }
+    /// Returns if the widget can take the keyboard focus, see
+    /// [`FocusManager`](crate::focus::FocusManager).
+    fn is_focusable(&self) -> bool {
+        false
+    }
+    /// Returns if the widget has the keyboard focus.
+    fn is_focused(&self) -> bool {
+        false
+    }
+    /// Gives the keyboard focus to the widget, or takes it away. Widgets which can't take the focus
+    /// ignore this.
+    fn set_focused(&mut self, _focused: bool) {}
+    /// Returns if the widget reacts to input, which it doesn't while it or a
+    /// [`Container`](crate::widgets::container::Container) around it is disabled.
+    fn is_enabled(&self) -> bool {
+        true
+    }
+    /// Tells the widget and its children if the container around them is enabled, see
+    /// [`Container::set_enabled`](crate::widgets::container::Container::set_enabled).
+    fn set_inherited_enabled(&mut self, enabled: bool) {
+        for child in self.children_mut() {
+            child.set_inherited_enabled(enabled);
+        }
+    }
+    /// Returns if the widget edits text with the keyboard while it has the focus, so the
+    /// [`FocusManager`](crate::focus::FocusManager) leaves the arrow keys, Enter and Space to it.
+    fn edits_text(&self) -> bool {
+        false
+    }
+    /// Activates the widget as if it was clicked, during its next update. This is how the confirm
+    /// action of the [`FocusManager`](crate::focus::FocusManager) clicks the focused widget.
+    fn activate(&mut self) {}
+    /// Returns where the widget was on the screen during the last update, if it keeps track of it.
+    /// It's used to move the focus by direction, so the focusable widgets remember the position
+    /// they were last updated at.
+    fn screen_rect(&self) -> Option<Rect> {
+        None
+    }
     /// Returns the width of the widget.
     fn width(&self) -> f32;
     /// Returns the height of the widget.
     fn height(&self) -> f32;
     /// Returns the background color of the widget.
     fn bg(&self) -> Color;
-    /// Updates the widget's state based on its position.
-    fn update(&mut self, x: f32, y: f32);
-    /// Renders the widget at the specified position.
-    fn render(&self, x: f32, y: f32);
+    /// Works out the size of the widget within the specified [`Constraints`], and the position of
+    /// its children, which are cached for [`Widget::update`] and [`Widget::render`]. It's called
+    /// once per frame, before both.
+    ///
+    /// By default the widget keeps its own width and height, clamped to the constraints.
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        constraints.constrain(Size::new(self.width(), self.height()))
+    }
+    /// Updates the widget's state based on its position and the input of the current frame.
+    fn update(&mut self, x: f32, y: f32, input: &mut InputState);
+    /// Renders the widget at the specified position through the specified [`Painter`].
+    fn render(&self, x: f32, y: f32, painter: &mut dyn Painter);
+}
+
+fn find_descendant<'a>(widget: &'a dyn Widget, id: &str) -> Option<&'a dyn Widget> {
+    for child in widget.children() {
+        if child.id() == Some(id) {
+            return Some(child);
+        }
+        if let Some(found) = find_descendant(child, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn find_descendant_mut<'a>(widget: &'a mut dyn Widget, id: &str) -> Option<&'a mut dyn Widget> {
+    for child in widget.children_mut() {
+        if child.id() == Some(id) {
+            return Some(child);
+        }
+        if let Some(found) = find_descendant_mut(child, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Collects the widgets under `widget` which can take the keyboard focus, in tree order.
+pub(crate) fn focusable_descendants<'a>(widget: &'a mut dyn Widget, found: &mut Vec<&'a mut dyn Widget>) {
+    for child in widget.children_mut() {
+        if child.is_focusable() {
+            found.push(child);
+        } else {
+            focusable_descendants(child, found);
+        }
+    }
+}
+
+fn focused_descendant(widget: &dyn Widget) -> Option<&dyn Widget> {
+    for child in widget.children() {
+        if child.is_focused() {
+            return Some(child);
+        }
+        if let Some(found) = focused_descendant(child) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+impl dyn Widget {
+    /// Finds a descendant widget by its ID path.
+    ///
+    /// The path is a list of IDs separated by dots, like `"login.password"`. Each ID is searched
+    /// for recursively inside the widget found by the previous one, starting with the children of
+    /// this widget.
+    pub fn find_widget(&self, path: &str) -> Option<&dyn Widget> {
+        path.split('.').try_fold(self, find_descendant)
+    }
+
+    /// Finds a descendant widget by its ID path, mutably. See `find_widget`.
+    pub fn find_widget_mut(&mut self, path: &str) -> Option<&mut dyn Widget> {
+        path.split('.').try_fold(self, find_descendant_mut)
+    }
+
+    /// Finds a descendant widget by its ID path and downcasts it to the specified type.
+    pub fn find<T: 'static>(&self, path: &str) -> Option<&T> {
+        self.find_widget(path)?.as_any().downcast_ref::<T>()
+    }
+
+    /// Finds a descendant widget by its ID path and mutably downcasts it to the specified type.
+    pub fn find_mut<T: 'static>(&mut self, path: &str) -> Option<&mut T> {
+        self.find_widget_mut(path)?.as_any_mut().downcast_mut::<T>()
+    }
+
+    /// Returns the descendant widget which has the keyboard focus, if there is one.
+    pub fn focused_widget(&self) -> Option<&dyn Widget> {
+        focused_descendant(self)
+    }
+
+    /// Gives the keyboard focus to the descendant widget with the specified ID path, and takes it
+    /// away from every other one. Returns false, leaving the focus where it was, if there's no
+    /// such widget or it can't take the focus.
+    pub fn focus(&mut self, path: &str) -> bool {
+        if !self.find_widget(path).is_some_and(|widget| widget.is_focusable()) {
+            return false;
+        }
+        self.clear_focus();
+        if let Some(widget) = self.find_widget_mut(path) {
+            widget.set_focused(true);
+        }
+        true
+    }
+
+    /// Takes the keyboard focus away from every descendant widget.
+    pub fn clear_focus(&mut self) {
+        let mut widgets = Vec::new();
+        focusable_descendants(self, &mut widgets);
+        for widget in widgets {
+            widget.set_focused(false);
+        }
+    }
+
+    /// Takes every [`Event`] queued by this widget and its descendants during the last update.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.drain_events(&mut events);
+        events
+    }
 }
 
 /// The [`Action`] trait which defines the actions that can be performed on UI elements.
@@ -24,4 +212,20 @@ pub trait Action {
     fn is_clicked(&self) -> bool;
     /// Returns if the widget is hovered.
     fn is_hovered(&self) -> bool;
+    /// Returns if the widget is held down with the left mouse button.
+    fn is_pressed(&self) -> bool {
+        false
+    }
+    /// Returns if the widget is clicked with the right mouse button.
+    fn is_right_clicked(&self) -> bool {
+        false
+    }
+    /// Returns if the widget is clicked with the middle mouse button.
+    fn is_middle_clicked(&self) -> bool {
+        false
+    }
+    /// Returns if the widget is clicked for the second time in quick succession.
+    fn is_double_clicked(&self) -> bool {
+        false
+    }
 }